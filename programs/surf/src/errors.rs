@@ -8,6 +8,12 @@ pub enum SurfError {
 
     #[msg("Quote token mint has to be USDC")]
     InvalidQuoteTokenMint,
+    #[msg("Payer base token account mint does not correspond with vault base token mint")]
+    InvalidPayerBaseTokenMint,
+    #[msg("Payer quote token account mint does not correspond with vault quote token mint")]
+    InvalidPayerQuoteTokenMint,
+    #[msg("Swap whirlpool token mints do not correspond with vault token mints")]
+    InvalidSwapWhirlpoolTokenMints,
 
     // Possibly change the amount of ticks later
     #[msg("Whirlpool position price range should be higher than 400 ticks")]
@@ -23,6 +29,26 @@ pub enum SurfError {
 
     #[msg("Could not deserialize drift_account_stats")]
     InvalidDriftAccountStatsAccount,
+    #[msg("Drift signer does not correspond with drift state signer")]
+    InvalidDriftSigner,
+    #[msg("Drift base spot market does not correspond with vault base spot market index")]
+    InvalidDriftBaseSpotMarket,
+    #[msg("Drift quote spot market does not correspond with vault quote spot market index")]
+    InvalidDriftQuoteSpotMarket,
+    #[msg("Drift base spot market mint does not correspond with base token mint")]
+    InvalidDriftBaseSpotMarketMint,
+    #[msg("Drift quote spot market mint does not correspond with quote token mint")]
+    InvalidDriftQuoteSpotMarketMint,
+    #[msg("Drift base spot market vault does not correspond with base spot market")]
+    InvalidDriftBaseSpotMarketVault,
+    #[msg("Drift quote spot market vault does not correspond with quote spot market")]
+    InvalidDriftQuoteSpotMarketVault,
+    #[msg("Drift perp market does not correspond with vault perp market index")]
+    InvalidDriftPerpMarket,
+    #[msg("Drift perp market oracle does not correspond with base spot market oracle")]
+    InvalidDriftPerpMarketOracle,
+    #[msg("Drift base token oracle does not correspond with base spot market oracle")]
+    InvalidDriftBaseTokenOracle,
 
     #[msg("Lower tick index must be lower than upper tick index")]
     InvalidTickIndexes,
@@ -30,6 +56,10 @@ pub enum SurfError {
     InvalidProvidedTickRange,
     #[msg("Current tick index is shifted too many ticks from middle of full tick range")]
     CurrentTickIndexShiftedFromMidRange,
+    #[msg("Lower tick array does not contain whirlpool position lower tick index")]
+    InvalidWhirlpoolPositionTickArrayLower,
+    #[msg("Upper tick array does not contain whirlpool position upper tick index")]
+    InvalidWhirlpoolPositionTickArrayUpper,
 
     #[msg("Tick index is either lower than -443636 or higher than 443636")]
     TickIndexOverflow,
@@ -39,4 +69,147 @@ pub enum SurfError {
 
     #[msg("Input quote amount is too high")]
     BaseTokenOverflow,
+    #[msg("Quote token amount overflow")]
+    QuoteTokenOverflow,
+    #[msg("Input quote amount is lower than quote amount required by whirlpool deposit")]
+    QuoteTokenUnderflow,
+    #[msg("Prepare swap did not decrease payer quote token amount")]
+    PrepareSwapQuoteTokenUnderflow,
+    #[msg("Upper sqrt price has to be higher than lower sqrt price")]
+    SqrtPriceUnderflow,
+    #[msg("Liquidity overflow")]
+    LiquidityOverflow,
+    #[msg("Liquidity underflow")]
+    LiquidityUnderflow,
+    #[msg("Liquidity is zero, input quote amount is too low")]
+    LiquidityZero,
+
+    #[msg("Drift subaccount health ratio would be lower than vault minimal health ratio")]
+    DriftHealthRatioTooLow,
+    #[msg("Deleverage target health ratio has to be higher than deleverage health ratio")]
+    InvalidDeleverageHealthRatios,
+    #[msg("Vault deleverage is disabled")]
+    DeleverageDisabled,
+    #[msg("Drift subaccount health ratio is above deleverage health ratio")]
+    HealthRatioAboveDeleverageThreshold,
+    #[msg("Deleverage did not improve drift subaccount health ratio")]
+    DeleverageDidNotImproveHealth,
+    #[msg("Vault hedge mode switch is disabled")]
+    HedgeModeSwitchDisabled,
+    #[msg("Other hedge instrument is not cheaper by switch threshold")]
+    HedgeModeSwitchNotBeneficial,
+    #[msg("Hedged base token amount changed more than allowed")]
+    HedgeDeltaOutOfTolerance,
+    #[msg("Max utilization has to be at most 1000000")]
+    InvalidMaxUtilization,
+    #[msg("Drift base spot market utilization after borrow would exceed vault max utilization")]
+    DriftUtilizationTooHigh,
+    #[msg("Drift base spot market borrow rate after borrow would exceed vault max borrow rate")]
+    DriftBorrowRateTooHigh,
+    #[msg("Hedge ratio has to be at most 10000 basis points")]
+    InvalidHedgeRatio,
+    #[msg("Target collateral ratio has to be higher than borrowed value")]
+    InvalidTargetCollateralRatio,
+    #[msg("Drift collateral rebalance is disabled")]
+    CollateralRebalanceDisabled,
+    #[msg("Drift collateral is already at target collateral ratio")]
+    DriftCollateralOnTarget,
+    #[msg("Vault has no idle quote tokens to deposit as drift collateral")]
+    InsufficientIdleQuoteTokens,
+    #[msg("Drift oracle price is invalid")]
+    InvalidDriftOraclePrice,
+    #[msg("Drift oracle price is stale")]
+    StaleDriftOraclePrice,
+    #[msg("Drift oracle price confidence interval is too wide")]
+    UncertainDriftOraclePrice,
+    #[msg("Whirlpool price deviates from drift oracle price more than allowed")]
+    WhirlpoolPriceDeviatesFromOracle,
+    #[msg("Could not calculate drift spot balance")]
+    DriftSpotBalanceError,
+    #[msg("Could not calculate drift perp position")]
+    DriftPerpPositionError,
+    #[msg("Math operation overflowed")]
+    MathOverflow,
+    #[msg("Net asset value overflow")]
+    NetAssetValueOverflow,
+    #[msg("Net asset value of vault is negative")]
+    NegativeNetAssetValue,
+    #[msg("Net asset value of vault with shares is zero")]
+    ZeroNetAssetValue,
+    #[msg("Deposit did not increase net asset value of vault")]
+    DepositValueZero,
+    #[msg("Deposit amount is lower than minimal deposit amount")]
+    DepositTooSmall,
+    #[msg("Depositor is not on vault allowlist")]
+    DepositorNotAllowlisted,
+    #[msg("Deposit would exceed vault deposit cap")]
+    VaultDepositCapExceeded,
+    #[msg("Deposit would exceed per user deposit cap")]
+    UserDepositCapExceeded,
+    #[msg("Shares overflow")]
+    SharesOverflow,
+    #[msg("Shares amount has to be higher than zero")]
+    SharesZero,
+    #[msg("Deposited shares amount is lower than minimal shares amount")]
+    DepositSharesTooLow,
+    #[msg("User position does not have enough shares")]
+    InsufficientShares,
+    #[msg("Fee overflow")]
+    FeeOverflow,
+
+    #[msg("Performance fee is higher than protocol maximum")]
+    PerformanceFeeTooHigh,
+    #[msg("Management fee is higher than protocol maximum")]
+    ManagementFeeTooHigh,
+    #[msg("Entry fee is higher than protocol maximum")]
+    EntryFeeTooHigh,
+    #[msg("Exit fee is higher than protocol maximum")]
+    ExitFeeTooHigh,
+    #[msg("Vault token account balance is lower than accrued protocol fees")]
+    ProtocolFeeExceedsBalance,
+    #[msg("Accrued protocol fees have to be collected")]
+    ProtocolFeesNotCollected,
+
+    #[msg("Vault has withdraw cooldown, withdraw has to be requested")]
+    WithdrawCooldownActive,
+    #[msg("Vault has no withdraw cooldown, withdraw instantly")]
+    WithdrawCooldownNotSet,
+    #[msg("Withdraw request cooldown has not elapsed yet")]
+    WithdrawCooldownNotElapsed,
+    #[msg("Withdrawn base token amount is lower than minimal base token amount")]
+    WithdrawBaseTokenAmountTooLow,
+    #[msg("Withdrawn quote token amount is lower than minimal quote token amount")]
+    WithdrawQuoteTokenAmountTooLow,
+
+    #[msg("Drift delegate has to be protocol drift keeper and can not be the vault")]
+    InvalidDriftDelegate,
+    #[msg("Drift subaccount delegate does not correspond with vault drift delegate")]
+    DriftDelegateMismatch,
+
+    #[msg("Vault is in recovery after drift subaccount liquidation")]
+    VaultInRecovery,
+    #[msg("Vault is not in recovery")]
+    VaultNotInRecovery,
+    #[msg("Drift subaccount is being liquidated or is bankrupt")]
+    DriftSubaccountBeingLiquidated,
+
+    #[msg("Vault whirlpool position is active")]
+    VaultPositionActive,
+    #[msg("Vault whirlpool position is not active")]
+    VaultPositionNotActive,
+    #[msg("Vault liquidity has to be zero")]
+    VaultLiquidityNotZero,
+    #[msg("Vault hedge has to be closed before its instrument is replaced")]
+    VaultHedgeNotClosed,
+    #[msg("Vault shares supply has to be zero")]
+    VaultSharesNotZero,
+    #[msg("Drift subaccount has open balances or positions")]
+    DriftSubaccountNotEmpty,
+
+    #[msg("User positions belong to different vaults")]
+    UserPositionVaultMismatch,
+    #[msg("User position has to have zero shares")]
+    UserPositionNotEmpty,
+    #[msg("Source and destination user position owners have to be different")]
+    SameUserPositionOwner,
 }
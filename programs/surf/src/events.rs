@@ -0,0 +1,352 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{VaultAccessMode, VaultHedgeMode};
+
+#[event]
+pub struct AdminConfigInitialized {
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub whirlpool: Pubkey,
+    pub base_token_mint: Pubkey,
+    pub quote_token_mint: Pubkey,
+    pub share_mint: Pubkey,
+    pub drift_subaccount: Pubkey,
+    pub full_tick_range: u32,
+    pub vault_tick_range: u32,
+    pub hedge_tick_range: u32,
+}
+
+#[event]
+pub struct WhirlpoolPositionOpened {
+    pub vault: Pubkey,
+    pub whirlpool_position: Pubkey,
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub vault_lower_tick_index: i32,
+    pub vault_upper_tick_index: i32,
+    pub tick_current_index: i32,
+}
+
+#[event]
+pub struct Deposited {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    // Quote tokens spent by owner, including prepare swap
+    pub input_quote_token_amount: u64,
+    pub liquidity_delta: u128,
+    pub whirlpool_base_token_amount: u64,
+    pub whirlpool_quote_token_amount: u64,
+    pub drift_deposit_quote_token_amount: u64,
+    pub drift_withdraw_base_token_amount: u64,
+    pub drift_perp_short_base_asset_amount: u64,
+    pub idle_base_token_amount_delta: i128,
+    pub idle_quote_token_amount_delta: i128,
+    pub shares: u64,
+    pub entry_fee_shares: u64,
+    pub tick_current_index: i32,
+    pub net_asset_value: u64,
+    pub vault_liquidity: u128,
+    pub vault_total_shares: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub exit_fee_shares: u64,
+    pub liquidity_delta: u128,
+    pub whirlpool_base_token_amount: u64,
+    pub whirlpool_quote_token_amount: u64,
+    // Base tokens bought with quote tokens to repay drift borrow
+    pub swap_base_token_amount: u64,
+    pub drift_deposit_base_token_amount: u64,
+    pub drift_perp_close_base_asset_amount: u64,
+    pub drift_withdraw_quote_token_amount: u64,
+    pub idle_base_token_amount: u64,
+    pub idle_quote_token_amount: u64,
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+    pub tick_current_index: i32,
+    pub vault_liquidity: u128,
+    pub vault_total_shares: u64,
+}
+
+#[event]
+pub struct WithdrawRequested {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub claimable_timestamp: i64,
+}
+
+#[event]
+pub struct UserPositionTransferred {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct UserPositionSplit {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct UserPositionsMerged {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct UserPositionClosed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct WhirlpoolFeesHarvested {
+    pub vault: Pubkey,
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+    pub idle_base_token_amount: u64,
+    pub idle_quote_token_amount: u64,
+}
+
+#[event]
+pub struct ManagementFeeAccrued {
+    pub vault: Pubkey,
+    pub fee_shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EntryFeeAccrued {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub fee_shares: u64,
+}
+
+#[event]
+pub struct ExitFeeAccrued {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub fee_shares: u64,
+}
+
+#[event]
+pub struct PerformanceFeeAccrued {
+    pub vault: Pubkey,
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub vault: Pubkey,
+    pub treasury: Pubkey,
+    pub fee_shares: u64,
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+}
+
+#[event]
+pub struct VaultIdleTokenAmountsSynced {
+    pub vault: Pubkey,
+    pub idle_base_token_amount: u64,
+    pub idle_quote_token_amount: u64,
+}
+
+#[event]
+pub struct VaultUnwound {
+    pub vault: Pubkey,
+    pub liquidity_delta: u128,
+    pub drift_deposit_base_token_amount: u64,
+    pub drift_perp_close_base_asset_amount: u64,
+    pub drift_withdraw_quote_token_amount: u64,
+    pub drift_withdraw_base_token_amount: u64,
+    // Vault token account balances after unwind
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub base_token_amount: u64,
+    pub quote_token_amount: u64,
+}
+
+#[event]
+pub struct ProtocolTreasuryUpdated {
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct ProtocolDriftKeeperUpdated {
+    pub drift_keeper: Pubkey,
+}
+
+#[event]
+pub struct VaultFeesUpdated {
+    pub vault: Pubkey,
+    pub performance_fee_bps: u16,
+    pub management_fee_bps: u16,
+    pub entry_fee_bps: u16,
+    pub exit_fee_bps: u16,
+}
+
+#[event]
+pub struct VaultDepositCapsUpdated {
+    pub vault: Pubkey,
+    pub max_total_deposit: u64,
+    pub max_deposit_per_user: u64,
+}
+
+#[event]
+pub struct VaultAccessModeUpdated {
+    pub vault: Pubkey,
+    pub access_mode: VaultAccessMode,
+}
+
+#[event]
+pub struct DepositorAllowlistEntryAdded {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+}
+
+#[event]
+pub struct DepositorAllowlistEntryRemoved {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+}
+
+#[event]
+pub struct VaultWithdrawCooldownUpdated {
+    pub vault: Pubkey,
+    pub withdraw_cooldown_seconds: u32,
+}
+
+#[event]
+pub struct VaultMinHealthRatioUpdated {
+    pub vault: Pubkey,
+    pub min_health_ratio: u64,
+}
+
+#[event]
+pub struct VaultDeleverageConfigUpdated {
+    pub vault: Pubkey,
+    pub deleverage_health_ratio: u64,
+    pub deleverage_target_health_ratio: u64,
+    pub deleverage_bounty_quote_amount: u64,
+}
+
+#[event]
+pub struct VaultDeleveraged {
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub liquidity_delta: u128,
+    pub whirlpool_base_token_amount: u64,
+    pub whirlpool_quote_token_amount: u64,
+    pub drift_deposit_base_token_amount: u64,
+    pub drift_perp_close_base_asset_amount: u64,
+    pub drift_deposit_quote_token_amount: u64,
+    pub bounty_quote_token_amount: u64,
+    pub pre_deleverage_health_ratio: u64,
+    pub post_deleverage_health_ratio: u64,
+    pub vault_liquidity: u128,
+}
+
+#[event]
+pub struct VaultRecoveryStarted {
+    pub vault: Pubkey,
+    pub drift_next_liquidation_id: u16,
+}
+
+#[event]
+pub struct VaultRecoveryResolved {
+    pub vault: Pubkey,
+    pub drift_next_liquidation_id: u16,
+}
+
+#[event]
+pub struct VaultTargetCollateralRatioUpdated {
+    pub vault: Pubkey,
+    pub target_collateral_ratio: u64,
+}
+
+#[event]
+pub struct DriftCollateralRebalanced {
+    pub vault: Pubkey,
+    pub drift_borrow_base_token_amount: u64,
+    pub drift_collateral_quote_token_amount: u64,
+    // Positive amount was deposited to drift from idle, negative withdrawn from drift to idle
+    pub quote_token_amount_delta: i128,
+    pub pre_rebalance_health_ratio: u64,
+    pub post_rebalance_health_ratio: u64,
+}
+
+#[event]
+pub struct VaultHedgeRatioUpdated {
+    pub vault: Pubkey,
+    pub hedge_ratio_bps: u16,
+}
+
+#[event]
+pub struct VaultHedgeModeUpdated {
+    pub vault: Pubkey,
+    pub hedge_mode: VaultHedgeMode,
+    pub drift_perp_market_index: u16,
+}
+
+#[event]
+pub struct VaultHedgeModeSwitchUpdated {
+    pub vault: Pubkey,
+    pub hedge_mode_switch_enabled: bool,
+    pub hedge_mode_switch_threshold_rate: u32,
+}
+
+#[event]
+pub struct HedgeModeSwitched {
+    pub vault: Pubkey,
+    pub hedge_mode: VaultHedgeMode,
+    pub spot_borrow_rate: i128,
+    pub perp_short_funding_rate: i128,
+    pub pre_hedge_base_token_amount: u64,
+    pub post_hedge_base_token_amount: u64,
+    pub drift_base_token_amount_delta: i128,
+    pub drift_perp_base_asset_amount_delta: i128,
+}
+
+#[event]
+pub struct VaultHedgeReduced {
+    pub vault: Pubkey,
+    pub hedge_base_token_amount: u64,
+    pub target_hedge_base_token_amount: u64,
+    pub drift_deposit_base_token_amount: u64,
+    pub drift_perp_close_base_asset_amount: u64,
+}
+
+#[event]
+pub struct VaultBorrowLimitsUpdated {
+    pub vault: Pubkey,
+    pub max_borrow_rate: u32,
+    pub max_utilization: u32,
+}
+
+#[event]
+pub struct VaultDriftDelegateUpdated {
+    pub vault: Pubkey,
+    pub drift_subaccount: Pubkey,
+    pub delegate: Pubkey,
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::DepositorAllowlistEntryAdded,
+    state::{AdminConfig, DepositorAllowlistEntry, Vault},
+    utils::constraints::is_admin,
+};
+
+pub fn handler(ctx: Context<AddDepositorAllowlistEntry>, depositor: Pubkey) -> Result<()> {
+    ctx.accounts
+        .depositor_allowlist_entry
+        .set_inner(DepositorAllowlistEntry {
+            bump: *ctx.bumps.get("depositor_allowlist_entry").unwrap(),
+            vault: ctx.accounts.vault.key(),
+            depositor,
+        });
+
+    emit!(DepositorAllowlistEntryAdded {
+        vault: ctx.accounts.vault.key(),
+        depositor,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(depositor: Pubkey)]
+pub struct AddDepositorAllowlistEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(init,
+        payer = admin,
+        space = DepositorAllowlistEntry::LEN,
+        seeds = [
+            DepositorAllowlistEntry::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            depositor.as_ref(),
+        ],
+        bump,
+    )]
+    pub depositor_allowlist_entry: Account<'info, DepositorAllowlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, TokenAccount};
+
+use crate::{
+    errors::SurfError, instructions::withdraw::WithdrawVaultAssets, state::WithdrawRequest,
+};
+
+// Redeems shares locked by request_withdraw once cooldown elapsed
+pub fn handler(ctx: Context<ClaimWithdraw>, min_base_out: u64, min_quote_out: u64) -> Result<()> {
+    let withdraw_request = &ctx.accounts.withdraw_request;
+    if Clock::get()?.unix_timestamp < withdraw_request.claimable_timestamp {
+        return Err(SurfError::WithdrawCooldownNotElapsed.into());
+    }
+    let shares = withdraw_request.shares;
+    let owner = withdraw_request.owner;
+
+    ctx.accounts
+        .vault_assets
+        .redeem_shares(shares, owner, min_base_out, min_quote_out)?;
+
+    let vault_key = ctx.accounts.vault_assets.vault.key();
+    let withdraw_request_signer_seeds: &[&[&[u8]]] = &[&[
+        WithdrawRequest::NAMESPACE.as_ref(),
+        vault_key.as_ref(),
+        owner.as_ref(),
+        &[ctx.accounts.withdraw_request.bump],
+    ]];
+    let token_program = &ctx.accounts.vault_assets.token_program;
+    token::burn(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx
+                    .accounts
+                    .withdraw_request_share_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.withdraw_request.to_account_info(),
+            },
+            withdraw_request_signer_seeds,
+        ),
+        shares,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: ctx
+                .accounts
+                .withdraw_request_share_token_account
+                .to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.withdraw_request.to_account_info(),
+        },
+        withdraw_request_signer_seeds,
+    ))?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimWithdraw<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub vault_assets: WithdrawVaultAssets<'info>,
+
+    #[account(mut,
+        close = owner,
+        seeds = [
+            WithdrawRequest::NAMESPACE.as_ref(),
+            vault_assets.vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = withdraw_request.bump,
+    )]
+    pub withdraw_request: Box<Account<'info, WithdrawRequest>>,
+
+    #[account(mut,
+        address = vault_assets.vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        associated_token::mint = share_mint,
+        associated_token::authority = withdraw_request,
+    )]
+    pub withdraw_request_share_token_account: Box<Account<'info, TokenAccount>>,
+}
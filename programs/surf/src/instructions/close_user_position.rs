@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::{
+    errors::SurfError,
+    events::UserPositionClosed,
+    state::{UserPosition, Vault},
+};
+
+pub fn handler(ctx: Context<CloseUserPosition>) -> Result<()> {
+    let user_position = &mut ctx.accounts.user_position;
+
+    user_position.sync_shares(ctx.accounts.owner_share_token_account.amount);
+    if !user_position.is_empty() {
+        return Err(SurfError::UserPositionNotEmpty.into());
+    }
+
+    emit!(UserPositionClosed {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.owner.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseUserPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        close = owner,
+        has_one = vault,
+        has_one = owner,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(
+        associated_token::mint = vault.share_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_share_token_account: Box<Account<'info, TokenAccount>>,
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use drift::{
+    cpi::{self as drift_cpi, accounts::DeleteUser},
+    program::Drift,
+    state::{
+        state::State as DriftState,
+        user::{User as DriftUser, UserStats as DriftUserStats},
+    },
+};
+
+use crate::{
+    errors::SurfError,
+    events::VaultClosed,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+pub fn handler(ctx: Context<CloseVault>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    if vault.is_active {
+        return Err(SurfError::VaultPositionActive.into());
+    }
+    if vault.liquidity != 0 {
+        return Err(SurfError::VaultLiquidityNotZero.into());
+    }
+    // Dead shares are never minted, supply is the amount of shares owned by users
+    if ctx.accounts.share_mint.supply != 0 {
+        return Err(SurfError::VaultSharesNotZero.into());
+    }
+    if vault.protocol_fee_shares != 0
+        || vault.protocol_fee_base_token_amount != 0
+        || vault.protocol_fee_quote_token_amount != 0
+    {
+        return Err(SurfError::ProtocolFeesNotCollected.into());
+    }
+    {
+        let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+        let has_spot_balances = drift_subaccount
+            .spot_positions
+            .iter()
+            .any(|spot_position| spot_position.scaled_balance != 0);
+        let has_perp_positions = drift_subaccount.perp_positions.iter().any(|perp_position| {
+            perp_position.base_asset_amount != 0 || perp_position.quote_asset_amount != 0
+        });
+        if has_spot_balances || has_perp_positions {
+            return Err(SurfError::DriftSubaccountNotEmpty.into());
+        }
+    }
+
+    let whirlpool_key = vault.whirlpool;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[vault.bump],
+    ]];
+
+    // --------
+    // Delete drift subaccount, rent is returned to vault and then to admin with vault
+    drift_cpi::delete_user(
+        ctx.accounts
+            .get_delete_user_context()
+            .with_signer(vault_signer_seeds),
+    )?;
+
+    // --------
+    // Close vault token accounts, dust left after withdrawals goes to admin
+    emit!(VaultClosed {
+        vault: ctx.accounts.vault.key(),
+        base_token_amount: ctx.accounts.vault_base_token_account.amount,
+        quote_token_amount: ctx.accounts.vault_quote_token_account.amount,
+    });
+
+    let token_program = &ctx.accounts.token_program;
+    let vault_token_accounts = [
+        (
+            &ctx.accounts.vault_base_token_account,
+            &ctx.accounts.admin_base_token_account,
+        ),
+        (
+            &ctx.accounts.vault_quote_token_account,
+            &ctx.accounts.admin_quote_token_account,
+        ),
+    ];
+    for (vault_token_account, admin_token_account) in vault_token_accounts {
+        if vault_token_account.amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: vault_token_account.to_account_info(),
+                        to: admin_token_account.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    vault_signer_seeds,
+                ),
+                vault_token_account.amount,
+            )?;
+        }
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: vault_token_account.to_account_info(),
+                destination: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            vault_signer_seeds,
+        ))?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        close = admin,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = vault.base_token_mint,
+    )]
+    pub admin_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = vault.quote_token_mint,
+    )]
+    pub admin_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ------
+    // Drift accounts
+    #[account(mut)]
+    pub drift_state: Box<Account<'info, DriftState>>,
+    #[account(mut,
+        address = vault.drift_stats.key(),
+    )]
+    pub drift_stats: AccountLoader<'info, DriftUserStats>,
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseVault<'info> {
+    pub fn get_delete_user_context(&self) -> CpiContext<'_, '_, '_, 'info, DeleteUser<'info>> {
+        let delete_user_accounts = DeleteUser {
+            user: self.drift_subaccount.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            state: self.drift_state.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext::new(self.drift_program.to_account_info(), delete_user_accounts)
+    }
+}
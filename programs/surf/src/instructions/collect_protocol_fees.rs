@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::{
+    events::{ManagementFeeAccrued, ProtocolFeesCollected},
+    state::{AdminConfig, UserPosition, Vault},
+};
+
+// Sends accrued protocol fee tokens and mints accrued fee shares to treasury
+pub fn handler(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let management_fee_shares = ctx
+        .accounts
+        .vault
+        .accrue_management_fee(current_timestamp)?;
+    if management_fee_shares > 0 {
+        emit!(ManagementFeeAccrued {
+            vault: ctx.accounts.vault.key(),
+            fee_shares: management_fee_shares,
+            timestamp: current_timestamp,
+        });
+    }
+
+    let whirlpool_key = ctx.accounts.vault.whirlpool;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+
+    let fee_shares = ctx.accounts.vault.protocol_fee_shares;
+    let base_token_amount = ctx.accounts.vault.protocol_fee_base_token_amount;
+    let quote_token_amount = ctx.accounts.vault.protocol_fee_quote_token_amount;
+
+    let token_program = &ctx.accounts.token_program;
+    if base_token_amount > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_base_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_base_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            base_token_amount,
+        )?;
+    }
+    if quote_token_amount > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_quote_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_quote_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            quote_token_amount,
+        )?;
+    }
+
+    // Treasury shares are tracked by user position like any other owner
+    let vault = &mut ctx.accounts.vault;
+    let treasury_user_position = &mut ctx.accounts.treasury_user_position;
+    if treasury_user_position.vault.eq(&Pubkey::default()) {
+        treasury_user_position.initialize(
+            *ctx.bumps.get("treasury_user_position").unwrap(),
+            vault.key(),
+            ctx.accounts.admin_config.treasury,
+        );
+    }
+    treasury_user_position.sync_shares(ctx.accounts.treasury_share_token_account.amount);
+    treasury_user_position.deposit(fee_shares)?;
+    vault.collect_protocol_fees();
+
+    if fee_shares > 0 {
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.treasury_share_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            fee_shares,
+        )?;
+    }
+
+    emit!(ProtocolFeesCollected {
+        vault: ctx.accounts.vault.key(),
+        treasury: ctx.accounts.admin_config.treasury,
+        fee_shares,
+        base_token_amount,
+        quote_token_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    // ------
+    // Treasury accounts
+    #[account(init_if_needed,
+        payer = payer,
+        space = UserPosition::LEN,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            admin_config.treasury.as_ref(),
+        ],
+        bump,
+    )]
+    pub treasury_user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut,
+        token::mint = vault.base_token_mint,
+        token::authority = admin_config.treasury,
+    )]
+    pub treasury_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = vault.quote_token_mint,
+        token::authority = admin_config.treasury,
+    )]
+    pub treasury_quote_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = admin_config.treasury,
+    )]
+    pub treasury_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
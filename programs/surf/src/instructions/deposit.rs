@@ -1,12 +1,18 @@
-use anchor_lang::{prelude::*, system_program};
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+};
 use drift::{
+    controller::position::PositionDirection,
     cpi::{
         self as drift_cpi,
-        accounts::{Deposit as DriftDeposit, Withdraw as DriftWithdraw},
+        accounts::{Deposit as DriftDeposit, PlaceAndTake, Withdraw as DriftWithdraw},
     },
     program::Drift,
     state::{
+        perp_market::PerpMarket,
+        spot_market::SpotMarket,
         state::State as DriftState,
         user::{User as DriftUser, UserStats as DriftUserStats},
     },
@@ -17,21 +23,70 @@ use whirlpools::{
     program::Whirlpool as WhirlpoolProgram,
     Position as WhirlpoolPosition, TickArray, Whirlpool,
 };
-use whirlpools_client::{
-    errors::ErrorCode,
-    math::{
-        get_amount_delta_a, get_amount_delta_b, sqrt_price_from_tick_index, MAX_SQRT_PRICE_X64,
-    },
+use whirlpools_client::math::{
+    get_amount_delta_a, get_amount_delta_b, sqrt_price_from_tick_index, MAX_SQRT_PRICE_X64,
 };
 
 use crate::{
     errors::SurfError,
-    state::{AdminConfig, UserPosition, Vault},
-    utils::orca::liquidity_math::{get_liquidity_from_base_token, get_liquidity_from_quote_token},
+    events::{Deposited, EntryFeeAccrued, ManagementFeeAccrued},
+    state::{
+        AdminConfig, DepositorAllowlistEntry, UserPosition, Vault, VaultAccessMode, VaultHedgeMode,
+        VaultStatus,
+    },
+    utils::{
+        constraints::{is_drift_perp_market, is_drift_spot_market, is_position_tick_array},
+        drift::{
+            margin::get_health_ratio,
+            oracle::{get_oracle_price, get_quote_value},
+            perp::{get_perp_market_order_params, get_perp_order_base_asset_amount},
+            rates::get_spot_market_borrow_conditions,
+        },
+        math::{mul_div, BPS_DENOMINATOR},
+        orca::liquidity_math::{get_liquidity_from_base_token, get_liquidity_from_quote_token},
+    },
 };
 
-pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()> {
-    // TODO: Update fees
+pub fn handler(
+    ctx: Context<Deposit>,
+    max_input_quote_amount: u64,
+    min_shares_out: u64,
+) -> Result<()> {
+    if max_input_quote_amount < Vault::MIN_DEPOSIT_QUOTE_AMOUNT {
+        return Err(SurfError::DepositTooSmall.into());
+    }
+    if ctx.accounts.vault.access_mode == VaultAccessMode::Allowlist {
+        Account::<DepositorAllowlistEntry>::try_from(&ctx.accounts.depositor_allowlist_entry)
+            .map_err(|_| SurfError::DepositorNotAllowlisted)?;
+    }
+    if ctx.accounts.vault.status == VaultStatus::Recovery
+        || ctx
+            .accounts
+            .vault
+            .is_drift_subaccount_liquidated(&*ctx.accounts.drift_subaccount.load()?)
+    {
+        return Err(SurfError::VaultInRecovery.into());
+    }
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let management_fee_shares = ctx
+        .accounts
+        .vault
+        .accrue_management_fee(current_timestamp)?;
+    if management_fee_shares > 0 {
+        emit!(ManagementFeeAccrued {
+            vault: ctx.accounts.vault.key(),
+            fee_shares: management_fee_shares,
+            timestamp: current_timestamp,
+        });
+    }
+
+    let base_token_oracle_price = ctx.accounts.get_base_token_oracle_price()?;
+    let pre_deposit_net_asset_value = ctx.accounts.get_net_asset_value(base_token_oracle_price)?;
+    let pre_deposit_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    let pre_deposit_vault_base_amount = ctx.accounts.vault_base_token_account.amount;
+    let pre_deposit_vault_quote_amount = ctx.accounts.vault_quote_token_account.amount;
+
     let lower_sqrt_price =
         sqrt_price_from_tick_index(ctx.accounts.whirlpool_position.tick_lower_index);
     let upper_sqrt_price =
@@ -45,7 +100,10 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
         lower_sqrt_price,
         upper_sqrt_price,
         false,
-    );
+    )?;
+    if estimated_whirlpool_liquidity_input == 0 {
+        return Err(SurfError::LiquidityZero.into());
+    }
     let current_sqrt_price = ctx.accounts.whirlpool.sqrt_price;
 
     let (estimated_base_input, estimated_quote_input) = get_whirlpool_input_tokens_deltas(
@@ -56,19 +114,23 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
     )?;
 
     let pre_swap_payer_quote_amount = ctx.accounts.payer_quote_token_account.amount;
+    let max_swap_quote_amount = ctx
+        .accounts
+        .get_max_swap_quote_amount(estimated_base_input, base_token_oracle_price)?;
     let whirlpool_prepare_swap_context = ctx.accounts.get_prepare_swap_context();
     whirlpool_cpi::swap(
         whirlpool_prepare_swap_context,
         estimated_base_input,
-        u64::MAX,
+        max_swap_quote_amount,
         MAX_SQRT_PRICE_X64,
         false,
         false,
     )?;
     ctx.accounts.payer_quote_token_account.reload()?;
     let post_swap_payer_quote_amount = ctx.accounts.payer_quote_token_account.amount;
-    let whirlpool_input_base_amount_denominated =
-        pre_swap_payer_quote_amount - post_swap_payer_quote_amount;
+    let whirlpool_input_base_amount_denominated = pre_swap_payer_quote_amount
+        .checked_sub(post_swap_payer_quote_amount)
+        .ok_or(SurfError::PrepareSwapQuoteTokenUnderflow)?;
 
     // -------
     // GET NEW DEPOSIT AMOUNTS
@@ -101,6 +163,13 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
         }
     }
 
+    if real_whirlpool_liquidity_input == 0 {
+        return Err(SurfError::LiquidityZero.into());
+    }
+    let vault_quote_input_amount = max_input_quote_amount
+        .checked_sub(whirlpool_input_base_amount_denominated)
+        .ok_or(SurfError::QuoteTokenUnderflow)?;
+
     // -------
     // TRANSFER TO VAULT ACCOUNTS
     let token_program = &ctx.accounts.token_program;
@@ -125,7 +194,7 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
                 authority: ctx.accounts.payer.to_account_info(),
             },
         ),
-        max_input_quote_amount - whirlpool_input_base_amount_denominated,
+        vault_quote_input_amount,
     )?;
 
     // -------
@@ -144,30 +213,176 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
 
     // -------
     // DEPOSIT TO DRIFT
-    let real_whirlpool_quote_input_amount =
-        whirlpool_input_base_amount_denominated + real_quote_input;
-    let drift_collateral_quote_amount = max_input_quote_amount - real_whirlpool_quote_input_amount;
+    let leftover_quote_amount = vault_quote_input_amount
+        .checked_sub(real_quote_input)
+        .ok_or(SurfError::QuoteTokenUnderflow)?;
+    // Unhedged vault skips drift, leftover quote tokens stay idle
+    let hedge_base_amount = ctx.accounts.vault.get_hedge_base_amount(real_base_input)?;
+    let drift_collateral_quote_amount = if hedge_base_amount > 0 {
+        leftover_quote_amount
+    } else {
+        0
+    };
 
     let whirlpool_key = ctx.accounts.whirlpool.key();
-    let drift_signer_seeds: &[&[&[u8]]] = &[&[
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
         Vault::NAMESPACE.as_ref(),
         whirlpool_key.as_ref(),
         &[ctx.accounts.vault.bump],
     ]];
-    let drift_deposit_context = ctx.accounts.get_drift_deposit_context(drift_signer_seeds);
-    drift_cpi::deposit(
-        drift_deposit_context,
-        0,
-        drift_collateral_quote_amount,
-        false,
-    )?;
-
-    // Withdraw from drift
-    let drift_withdraw_context = ctx.accounts.get_drift_withdraw_context(drift_signer_seeds);
-    drift_cpi::withdraw(drift_withdraw_context, 1, real_base_input, false)?;
+    let mut drift_withdraw_base_token_amount = 0;
+    let mut drift_perp_short_base_asset_amount = 0;
+    if hedge_base_amount > 0 {
+        let drift_deposit_context = ctx.accounts.get_drift_deposit_context(vault_signer_seeds);
+        drift_cpi::deposit(
+            drift_deposit_context,
+            ctx.accounts.vault.drift_quote_spot_market_index,
+            drift_collateral_quote_amount,
+            false,
+        )?;
+
+        match ctx.accounts.vault.hedge_mode {
+            VaultHedgeMode::SpotBorrow => {
+                let (utilization, borrow_rate) = get_spot_market_borrow_conditions(
+                    &*ctx.accounts.drift_base_spot_market.load()?,
+                    hedge_base_amount,
+                )?;
+                ctx.accounts
+                    .vault
+                    .validate_borrow_conditions(utilization, borrow_rate)?;
+
+                // Withdraw from drift
+                let drift_withdraw_context =
+                    ctx.accounts.get_drift_withdraw_context(vault_signer_seeds);
+                drift_cpi::withdraw(
+                    drift_withdraw_context,
+                    ctx.accounts.vault.drift_base_spot_market_index,
+                    hedge_base_amount,
+                    false,
+                )?;
+                drift_withdraw_base_token_amount = hedge_base_amount;
+            }
+            VaultHedgeMode::PerpShort => {
+                drift_perp_short_base_asset_amount = get_perp_order_base_asset_amount(
+                    hedge_base_amount,
+                    ctx.accounts.drift_base_spot_market.load()?.decimals,
+                    ctx.accounts.drift_perp_market.load()?.amm.order_step_size,
+                )
+                .ok_or(SurfError::BaseTokenOverflow)?;
+                if drift_perp_short_base_asset_amount > 0 {
+                    drift_cpi::place_and_take_perp_order(
+                        ctx.accounts
+                            .get_drift_place_and_take_context(vault_signer_seeds),
+                        get_perp_market_order_params(
+                            PositionDirection::Short,
+                            drift_perp_short_base_asset_amount,
+                            ctx.accounts.vault.drift_perp_market_index,
+                            false,
+                            base_token_oracle_price,
+                            Vault::MAX_SWAP_SLIPPAGE_BPS,
+                        )?,
+                        None,
+                    )?;
+                }
+            }
+        }
+    }
 
     // Swap withdrawn SOL from drift
 
+    let post_deposit_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    ctx.accounts
+        .vault
+        .validate_health_ratio(pre_deposit_health_ratio, post_deposit_health_ratio)?;
+
+    // -------
+    // UPDATE SHARES
+    ctx.accounts.whirlpool.reload()?;
+    ctx.accounts.whirlpool_position.reload()?;
+    ctx.accounts.vault_base_token_account.reload()?;
+    ctx.accounts.vault_quote_token_account.reload()?;
+    let base_token_amount_delta = (ctx.accounts.vault_base_token_account.amount as i128)
+        - (pre_deposit_vault_base_amount as i128);
+    let quote_token_amount_delta = (ctx.accounts.vault_quote_token_account.amount as i128)
+        - (pre_deposit_vault_quote_amount as i128);
+    ctx.accounts
+        .vault
+        .update_idle_token_amounts(base_token_amount_delta, quote_token_amount_delta)?;
+    let post_deposit_net_asset_value = ctx.accounts.get_net_asset_value(base_token_oracle_price)?;
+
+    let deposit_value = post_deposit_net_asset_value
+        .checked_sub(pre_deposit_net_asset_value)
+        .filter(|deposit_value| *deposit_value > 0)
+        .ok_or(SurfError::DepositValueZero)?;
+    let shares = ctx
+        .accounts
+        .vault
+        .get_shares_from_deposit_value(deposit_value, pre_deposit_net_asset_value)?;
+    let entry_fee_shares = Vault::get_fee_shares(shares, ctx.accounts.vault.entry_fee_bps)?;
+    let user_shares = shares - entry_fee_shares;
+    if user_shares == 0 {
+        return Err(SurfError::SharesZero.into());
+    }
+    if user_shares < min_shares_out {
+        return Err(SurfError::DepositSharesTooLow.into());
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    let user_position = &mut ctx.accounts.user_position;
+    if user_position.vault.eq(&Pubkey::default()) {
+        user_position.initialize(
+            *ctx.bumps.get("user_position").unwrap(),
+            vault.key(),
+            ctx.accounts.payer.key(),
+        );
+    }
+    user_position.sync_shares(ctx.accounts.payer_share_token_account.amount);
+    user_position.deposit(user_shares)?;
+    vault.deposit(real_whirlpool_liquidity_input, user_shares)?;
+    if entry_fee_shares > 0 {
+        vault.accrue_protocol_fee_shares(entry_fee_shares)?;
+        emit!(EntryFeeAccrued {
+            vault: vault.key(),
+            owner: ctx.accounts.payer.key(),
+            fee_shares: entry_fee_shares,
+        });
+    }
+    vault.check_deposit_caps(post_deposit_net_asset_value, user_position.shares)?;
+
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.payer_share_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            vault_signer_seeds,
+        ),
+        user_shares,
+    )?;
+
+    let vault = &ctx.accounts.vault;
+    emit!(Deposited {
+        vault: vault.key(),
+        owner: ctx.accounts.payer.key(),
+        input_quote_token_amount: max_input_quote_amount,
+        liquidity_delta: real_whirlpool_liquidity_input,
+        whirlpool_base_token_amount: real_base_input,
+        whirlpool_quote_token_amount: real_quote_input,
+        drift_deposit_quote_token_amount: drift_collateral_quote_amount,
+        drift_withdraw_base_token_amount,
+        drift_perp_short_base_asset_amount,
+        idle_base_token_amount_delta: base_token_amount_delta,
+        idle_quote_token_amount_delta: quote_token_amount_delta,
+        shares: user_shares,
+        entry_fee_shares,
+        tick_current_index: ctx.accounts.whirlpool.tick_current_index,
+        net_asset_value: post_deposit_net_asset_value,
+        vault_liquidity: vault.liquidity,
+        vault_total_shares: vault.total_shares,
+    });
+
     Ok(())
 }
 
@@ -175,13 +390,12 @@ pub fn handler(ctx: Context<Deposit>, max_input_quote_amount: u64) -> Result<()>
 pub struct Deposit<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    // TODO: Add constraints
     #[account(mut,
-        constraint = payer_base_token_account.mint.key().eq(&prepare_swap_whirlpool.token_mint_a.key())
+        constraint = payer_base_token_account.mint.eq(&vault.base_token_mint) @SurfError::InvalidPayerBaseTokenMint,
     )]
     pub payer_base_token_account: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = payer_quote_token_account.mint.key().eq(&prepare_swap_whirlpool.token_mint_b.key())
+        constraint = payer_quote_token_account.mint.eq(&vault.quote_token_mint) @SurfError::InvalidPayerQuoteTokenMint,
     )]
     pub payer_quote_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -193,9 +407,46 @@ pub struct Deposit<'info> {
     )]
     pub admin_config: Box<Account<'info, AdminConfig>>,
 
+    #[account(init_if_needed,
+        payer = payer,
+        space = UserPosition::LEN,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// CHECK: Has to be initialized only if vault access mode is allowlist
+    #[account(
+        seeds = [
+            DepositorAllowlistEntry::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub depositor_allowlist_entry: UncheckedAccount<'info>,
+
+    #[account(mut,
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(init_if_needed,
+        payer = payer,
+        associated_token::mint = share_mint,
+        associated_token::authority = payer,
+    )]
+    pub payer_share_token_account: Box<Account<'info, TokenAccount>>,
+
     // -------------
     // Swap accounts
-    #[account(mut)]
+    #[account(mut,
+        constraint = prepare_swap_whirlpool.token_mint_a.eq(&vault.base_token_mint)
+            && prepare_swap_whirlpool.token_mint_b.eq(&vault.quote_token_mint) @SurfError::InvalidSwapWhirlpoolTokenMints,
+    )]
     pub prepare_swap_whirlpool: Box<Account<'info, Whirlpool>>,
 
     #[account(mut, address = prepare_swap_whirlpool.token_vault_a)]
@@ -256,10 +507,23 @@ pub struct Deposit<'info> {
     )]
     pub whirlpool_position_token_account: Account<'info, TokenAccount>,
 
-    // Whirlpool program performs checks
-    #[account(mut)]
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_lower,
+            &whirlpool.key(),
+            whirlpool_position.tick_lower_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayLower,
+    )]
     pub whirlpool_position_tick_array_lower: AccountLoader<'info, TickArray>,
-    #[account(mut)]
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_upper,
+            &whirlpool.key(),
+            whirlpool_position.tick_upper_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayUpper,
+    )]
     pub whirlpool_position_tick_array_upper: AccountLoader<'info, TickArray>,
 
     #[account(mut)]
@@ -277,30 +541,46 @@ pub struct Deposit<'info> {
     // ----------------
     // Drift accounts
     pub drift_state: Box<Account<'info, DriftState>>,
-    /// CHECK: Drift program handles checks
+    /// CHECK: Address is validated against drift state signer
+    #[account(
+        address = drift_state.signer @SurfError::InvalidDriftSigner,
+    )]
     pub drift_signer: UncheckedAccount<'info>,
     #[account(mut,
-        seeds = [b"spot_market_vault".as_ref(), 0_u16.to_le_bytes().as_ref()],
-        bump,
-        seeds::program = drift_program.key(),
+        address = drift_quote_spot_market.load()?.vault @SurfError::InvalidDriftQuoteSpotMarketVault,
     )]
     pub drift_quote_spot_market_vault: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        seeds = [b"spot_market_vault".as_ref(), 1_u16.to_le_bytes().as_ref()],
-        bump,
-        seeds::program = drift_program.key(),
+        address = drift_base_spot_market.load()?.vault @SurfError::InvalidDriftBaseSpotMarketVault,
     )]
     pub drift_base_spot_market_vault: Box<Account<'info, TokenAccount>>,
 
-    // TODO: Add custom errors ??
-    /// CHECK: Drift program checks these accounts
+    /// CHECK: Address is validated against drift base spot market oracle
+    #[account(
+        address = drift_base_spot_market.load()?.oracle @SurfError::InvalidDriftBaseTokenOracle,
+    )]
     pub drift_base_token_oracle: UncheckedAccount<'info>,
-    /// CHECK: Drift program checks these accounts
-    #[account(mut)]
-    pub drift_base_spot_market: UncheckedAccount<'info>,
-    /// CHECK: Drift program checks these accounts
-    #[account(mut)]
-    pub drift_quote_spot_market: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_quote_spot_market,
+            vault.drift_quote_spot_market_index,
+        ) @SurfError::InvalidDriftQuoteSpotMarket,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_perp_market(
+            &drift_perp_market,
+            vault.drift_perp_market_index,
+        ) @SurfError::InvalidDriftPerpMarket,
+    )]
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
 
     #[account(mut,
         address = vault.drift_stats.key(),
@@ -314,10 +594,64 @@ pub struct Deposit<'info> {
     pub whirlpool_program: Program<'info, WhirlpoolProgram>,
     pub drift_program: Program<'info, Drift>,
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 impl<'info> Deposit<'info> {
+    pub fn get_base_token_oracle_price(&self) -> Result<i64> {
+        let drift_base_spot_market = self.drift_base_spot_market.load()?;
+        get_oracle_price(
+            &self.drift_base_token_oracle.to_account_info(),
+            &drift_base_spot_market,
+            &self.drift_state.oracle_guard_rails,
+            Clock::get()?.slot,
+        )
+    }
+
+    pub fn get_net_asset_value(&self, base_token_oracle_price: i64) -> Result<u64> {
+        self.vault.net_asset_value(
+            &self.whirlpool_position,
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
+    // Prepare swap can pay at most max slippage over oracle value of bought base tokens
+    pub fn get_max_swap_quote_amount(
+        &self,
+        base_token_amount: u64,
+        base_token_oracle_price: i64,
+    ) -> Result<u64> {
+        let base_token_value = get_quote_value(
+            base_token_amount as i128,
+            base_token_oracle_price,
+            self.drift_base_spot_market.load()?.decimals,
+        )?;
+        mul_div(
+            base_token_value.unsigned_abs(),
+            (BPS_DENOMINATOR + Vault::MAX_SWAP_SLIPPAGE_BPS) as u128,
+            BPS_DENOMINATOR as u128,
+            true,
+        )
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(SurfError::QuoteTokenOverflow.into())
+    }
+
+    pub fn get_health_ratio(&self, base_token_oracle_price: i64) -> Result<u64> {
+        get_health_ratio(
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
     pub fn get_prepare_swap_context(&self) -> CpiContext<'_, '_, '_, 'info, Swap<'info>> {
         let init_swap_accounts = Swap {
             token_authority: self.payer.to_account_info(),
@@ -361,6 +695,16 @@ impl<'info> Deposit<'info> {
         )
     }
 
+    // Drift expects oracles first, then spot markets and perp markets
+    pub fn get_drift_remaining_accounts(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.drift_base_token_oracle.to_account_info(),
+            self.drift_quote_spot_market.to_account_info(),
+            self.drift_base_spot_market.to_account_info(),
+            self.drift_perp_market.to_account_info(),
+        ]
+    }
+
     pub fn get_drift_deposit_context<'a>(
         &'a self,
         signer_seeds: &'a [&[&[u8]]],
@@ -377,10 +721,25 @@ impl<'info> Deposit<'info> {
         CpiContext {
             program: self.drift_program.to_account_info(),
             accounts: deposit_accounts,
-            remaining_accounts: vec![
-                self.drift_quote_spot_market.to_account_info(),
-                self.drift_base_token_oracle.to_account_info(),
-            ],
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_place_and_take_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, PlaceAndTake<'info>> {
+        let place_and_take_accounts = PlaceAndTake {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: place_and_take_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
             signer_seeds,
         }
     }
@@ -402,10 +761,7 @@ impl<'info> Deposit<'info> {
         CpiContext {
             program: self.drift_program.to_account_info(),
             accounts: withdraw_accounts,
-            remaining_accounts: vec![
-                self.drift_base_spot_market.to_account_info(),
-                self.drift_base_token_oracle.to_account_info(),
-            ],
+            remaining_accounts: self.get_drift_remaining_accounts(),
             signer_seeds,
         }
     }
@@ -418,17 +774,11 @@ pub fn get_whirlpool_input_tokens_deltas(
     lower_sqrt_price: u128,
 ) -> Result<(u64, u64)> {
     let base_token_amount =
-        get_amount_delta_a(current_sqrt_price, upper_sqrt_price, liquidity_input, true);
+        get_amount_delta_a(current_sqrt_price, upper_sqrt_price, liquidity_input, true)
+            .map_err(|_| SurfError::BaseTokenOverflow)?;
     let quote_token_amount =
-        get_amount_delta_b(lower_sqrt_price, current_sqrt_price, liquidity_input, true);
-
-    // TODO: Handle errors
-    if let Err(err) = base_token_amount {
-        return Err(SurfError::BaseTokenOverflow.into());
-    }
-    if let Err(_) = quote_token_amount {
-        return Err(SurfError::BaseTokenOverflow.into());
-    }
+        get_amount_delta_b(lower_sqrt_price, current_sqrt_price, liquidity_input, true)
+            .map_err(|_| SurfError::QuoteTokenOverflow)?;
 
-    Ok((base_token_amount.unwrap(), quote_token_amount.unwrap()))
+    Ok((base_token_amount, quote_token_amount))
 }
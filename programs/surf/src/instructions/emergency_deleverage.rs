@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Transfer};
+use drift::{controller::position::PositionDirection, cpi as drift_cpi};
+use whirlpools::cpi as whirlpool_cpi;
+use whirlpools_client::math::sqrt_price_from_tick_index;
+
+use crate::{
+    errors::SurfError,
+    events::VaultDeleveraged,
+    instructions::withdraw::WithdrawVaultAssets,
+    state::{Vault, VaultHedgeMode},
+    utils::{
+        drift::{
+            margin::{calculate_health_ratio, get_deleverage_liquidity},
+            oracle::{get_oracle_sqrt_price, get_quote_value},
+            perp::{
+                get_perp_base_asset_amount, get_perp_market_order_params,
+                get_perp_order_base_asset_amount,
+            },
+            spot_balance::get_spot_token_amount,
+        },
+        orca::position_math::get_token_amounts_from_liquidity,
+    },
+};
+
+// Permissionless, pulls liquidity from whirlpool to reduce drift hedge until target health ratio is reached
+pub fn handler(ctx: Context<EmergencyDeleverage>) -> Result<()> {
+    let vault_assets = &mut ctx.accounts.vault_assets;
+    vault_assets.update_vault_status()?;
+    if vault_assets.vault.deleverage_health_ratio == 0 {
+        return Err(SurfError::DeleverageDisabled.into());
+    }
+
+    let base_token_oracle_price = vault_assets.get_base_token_oracle_price()?;
+    let (total_collateral, maintenance_margin_requirement) =
+        vault_assets.get_margin(base_token_oracle_price)?;
+    let pre_deleverage_health_ratio =
+        calculate_health_ratio(total_collateral, maintenance_margin_requirement)?;
+    if pre_deleverage_health_ratio >= vault_assets.vault.deleverage_health_ratio {
+        return Err(SurfError::HealthRatioAboveDeleverageThreshold.into());
+    }
+
+    // -------
+    // COMPUTE LIQUIDITY
+    // Position is split at oracle price, so whirlpool price moves can not change the sizing
+    let oracle_sqrt_price = get_oracle_sqrt_price(
+        base_token_oracle_price,
+        vault_assets.drift_base_spot_market.load()?.decimals,
+        vault_assets.drift_quote_spot_market.load()?.decimals,
+    )?;
+    let position_liquidity = vault_assets.whirlpool_position.liquidity;
+    let (position_base_token_amount, position_quote_token_amount) =
+        get_token_amounts_from_liquidity(
+            position_liquidity,
+            oracle_sqrt_price,
+            sqrt_price_from_tick_index(vault_assets.whirlpool_position.tick_lower_index),
+            sqrt_price_from_tick_index(vault_assets.whirlpool_position.tick_upper_index),
+            false,
+        )?;
+    let (
+        base_liability_weight,
+        quote_asset_weight,
+        drift_borrow_amount,
+        drift_perp_base_asset_amount,
+    ) = {
+        let drift_subaccount = vault_assets.drift_subaccount.load()?;
+        let drift_base_spot_market = vault_assets.drift_base_spot_market.load()?;
+        let drift_quote_spot_market = vault_assets.drift_quote_spot_market.load()?;
+        let drift_perp_market = vault_assets.drift_perp_market.load()?;
+        let drift_base_token_amount =
+            get_spot_token_amount(&drift_subaccount, &drift_base_spot_market)?;
+        // Freed base tokens lower the requirement of the instrument hedging them
+        // Perp margin ratio and spot weights share the same precision
+        let base_liability_weight = match vault_assets.vault.hedge_mode {
+            VaultHedgeMode::SpotBorrow => drift_base_spot_market.maintenance_liability_weight,
+            VaultHedgeMode::PerpShort => drift_perp_market.margin_ratio_maintenance,
+        };
+        (
+            base_liability_weight,
+            drift_quote_spot_market.maintenance_asset_weight,
+            u64::try_from(-drift_base_token_amount.min(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            get_perp_base_asset_amount(&drift_subaccount, &drift_perp_market),
+        )
+    };
+    let position_base_token_value = get_quote_value(
+        position_base_token_amount as i128,
+        base_token_oracle_price,
+        vault_assets.drift_base_spot_market.load()?.decimals,
+    )?;
+
+    let liquidity = get_deleverage_liquidity(
+        position_liquidity,
+        position_base_token_value as u128,
+        position_quote_token_amount as u128,
+        total_collateral,
+        maintenance_margin_requirement,
+        vault_assets.vault.deleverage_target_health_ratio,
+        base_liability_weight,
+        quote_asset_weight,
+    )
+    .ok_or(SurfError::LiquidityOverflow)?;
+    if liquidity == 0 {
+        return Err(SurfError::LiquidityZero.into());
+    }
+
+    let whirlpool_key = vault_assets.whirlpool.key();
+    let vault_bump = vault_assets.vault.bump;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[vault_bump],
+    ]];
+
+    // -------
+    // WITHDRAW FROM WHIRLPOOL
+    let pre_deleverage_vault_base_amount = vault_assets.vault_base_token_account.amount;
+    let pre_deleverage_vault_quote_amount = vault_assets.vault_quote_token_account.amount;
+    let (min_base_token_amount, min_quote_token_amount) =
+        vault_assets.get_decrease_liquidity_min_amounts(liquidity, base_token_oracle_price)?;
+    whirlpool_cpi::decrease_liquidity(
+        vault_assets
+            .get_whirlpool_decrease_liquidity_context()
+            .with_signer(vault_signer_seeds),
+        liquidity,
+        min_base_token_amount,
+        min_quote_token_amount,
+    )?;
+    vault_assets.vault_base_token_account.reload()?;
+    vault_assets.vault_quote_token_account.reload()?;
+    let whirlpool_base_token_amount = vault_assets
+        .vault_base_token_account
+        .amount
+        .checked_sub(pre_deleverage_vault_base_amount)
+        .ok_or(SurfError::BaseTokenOverflow)?;
+    let whirlpool_quote_token_amount = vault_assets
+        .vault_quote_token_account
+        .amount
+        .checked_sub(pre_deleverage_vault_quote_amount)
+        .ok_or(SurfError::QuoteTokenUnderflow)?;
+
+    // -------
+    // REDUCE DRIFT HEDGE
+    let (repay_base_amount, perp_close_base_asset_amount) = match vault_assets.vault.hedge_mode {
+        VaultHedgeMode::SpotBorrow => {
+            let repay_base_amount = whirlpool_base_token_amount.min(drift_borrow_amount);
+            if repay_base_amount > 0 {
+                drift_cpi::deposit(
+                    vault_assets.get_drift_deposit_context(vault_signer_seeds),
+                    vault_assets.vault.drift_base_spot_market_index,
+                    repay_base_amount,
+                    true,
+                )?;
+            }
+            (repay_base_amount, 0)
+        }
+        // Perp short is closed by the freed base amount, freed base tokens stay idle in vault
+        VaultHedgeMode::PerpShort => {
+            let perp_close_base_asset_amount = get_perp_order_base_asset_amount(
+                whirlpool_base_token_amount,
+                vault_assets.drift_base_spot_market.load()?.decimals,
+                vault_assets.drift_perp_market.load()?.amm.order_step_size,
+            )
+            .ok_or(SurfError::BaseTokenOverflow)?
+            .min(drift_perp_base_asset_amount.min(0).unsigned_abs());
+            if perp_close_base_asset_amount > 0 {
+                drift_cpi::place_and_take_perp_order(
+                    vault_assets.get_drift_place_and_take_context(vault_signer_seeds),
+                    get_perp_market_order_params(
+                        PositionDirection::Long,
+                        perp_close_base_asset_amount,
+                        vault_assets.vault.drift_perp_market_index,
+                        true,
+                        base_token_oracle_price,
+                        Vault::MAX_SWAP_SLIPPAGE_BPS,
+                    )?,
+                    None,
+                )?;
+            }
+            // Realized pnl is settled into quote collateral
+            if drift_perp_base_asset_amount != 0 {
+                drift_cpi::settle_pnl(
+                    vault_assets.get_drift_settle_pnl_context(vault_signer_seeds),
+                    vault_assets.vault.drift_perp_market_index,
+                )?;
+            }
+            (0, perp_close_base_asset_amount)
+        }
+    };
+
+    // Quote tokens from whirlpool are added as drift collateral
+    if whirlpool_quote_token_amount > 0 {
+        drift_cpi::deposit(
+            vault_assets.get_drift_collateral_deposit_context(vault_signer_seeds),
+            vault_assets.vault.drift_quote_spot_market_index,
+            whirlpool_quote_token_amount,
+            false,
+        )?;
+    }
+
+    let post_deleverage_health_ratio = vault_assets.get_health_ratio(base_token_oracle_price)?;
+    if post_deleverage_health_ratio <= pre_deleverage_health_ratio {
+        return Err(SurfError::DeleverageDidNotImproveHealth.into());
+    }
+
+    // -------
+    // PAY BOUNTY
+    let bounty_quote_amount = vault_assets.vault.pay_deleverage_bounty();
+    if bounty_quote_amount > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                vault_assets.token_program.to_account_info(),
+                Transfer {
+                    from: vault_assets.vault_quote_token_account.to_account_info(),
+                    to: vault_assets.payer_quote_token_account.to_account_info(),
+                    authority: vault_assets.vault.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            bounty_quote_amount,
+        )?;
+    }
+
+    // -------
+    // UPDATE VAULT
+    // Base tokens left after the hedge is reduced stay idle in vault
+    let vault = &mut vault_assets.vault;
+    vault.withdraw(liquidity, 0)?;
+    vault
+        .update_idle_token_amounts((whirlpool_base_token_amount - repay_base_amount) as i128, 0)?;
+
+    emit!(VaultDeleveraged {
+        vault: vault.key(),
+        caller: ctx.accounts.caller.key(),
+        liquidity_delta: liquidity,
+        whirlpool_base_token_amount,
+        whirlpool_quote_token_amount,
+        drift_deposit_base_token_amount: repay_base_amount,
+        drift_perp_close_base_asset_amount: perp_close_base_asset_amount,
+        drift_deposit_quote_token_amount: whirlpool_quote_token_amount,
+        bounty_quote_token_amount: bounty_quote_amount,
+        pre_deleverage_health_ratio,
+        post_deleverage_health_ratio,
+        vault_liquidity: vault.liquidity,
+    });
+
+    // Hedge of freed base tokens left idle is reduced to vault hedge ratio
+    vault_assets.reduce_hedge_to_ratio(base_token_oracle_price, vault_signer_seeds)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EmergencyDeleverage<'info> {
+    pub caller: Signer<'info>,
+
+    // Payer token accounts of vault assets belong to caller and receive the bounty
+    pub vault_assets: WithdrawVaultAssets<'info>,
+}
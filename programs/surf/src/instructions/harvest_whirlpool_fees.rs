@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use whirlpools::{
+    cpi as whirlpool_cpi,
+    cpi::accounts::{CollectFees, UpdateFeesAndRewards},
+    program::Whirlpool as WhirlpoolProgram,
+    Position as WhirlpoolPosition, TickArray, Whirlpool,
+};
+
+use crate::{
+    errors::SurfError,
+    events::{PerformanceFeeAccrued, WhirlpoolFeesHarvested},
+    state::Vault,
+    utils::constraints::is_position_tick_array,
+};
+
+// Collects whirlpool position fees, performance fee goes to protocol and the rest is compounded
+pub fn handler(ctx: Context<HarvestWhirlpoolFees>) -> Result<()> {
+    let pre_harvest_vault_base_amount = ctx.accounts.vault_base_token_account.amount;
+    let pre_harvest_vault_quote_amount = ctx.accounts.vault_quote_token_account.amount;
+
+    let whirlpool_key = ctx.accounts.whirlpool.key();
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+
+    whirlpool_cpi::update_fees_and_rewards(ctx.accounts.get_update_fees_and_rewards_context())?;
+    whirlpool_cpi::collect_fees(
+        ctx.accounts
+            .get_collect_fees_context()
+            .with_signer(vault_signer_seeds),
+    )?;
+
+    ctx.accounts.vault_base_token_account.reload()?;
+    ctx.accounts.vault_quote_token_account.reload()?;
+    let base_token_fee = ctx
+        .accounts
+        .vault_base_token_account
+        .amount
+        .checked_sub(pre_harvest_vault_base_amount)
+        .ok_or(SurfError::BaseTokenOverflow)?;
+    let quote_token_fee = ctx
+        .accounts
+        .vault_quote_token_account
+        .amount
+        .checked_sub(pre_harvest_vault_quote_amount)
+        .ok_or(SurfError::QuoteTokenUnderflow)?;
+
+    let vault = &mut ctx.accounts.vault;
+    let (base_token_performance_fee, quote_token_performance_fee) =
+        vault.harvest_fees(base_token_fee, quote_token_fee)?;
+    if base_token_performance_fee > 0 || quote_token_performance_fee > 0 {
+        emit!(PerformanceFeeAccrued {
+            vault: vault.key(),
+            base_token_amount: base_token_performance_fee,
+            quote_token_amount: quote_token_performance_fee,
+        });
+    }
+    emit!(WhirlpoolFeesHarvested {
+        vault: vault.key(),
+        base_token_amount: base_token_fee,
+        quote_token_amount: quote_token_fee,
+        idle_base_token_amount: vault.idle_base_token_amount,
+        idle_quote_token_amount: vault.idle_quote_token_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct HarvestWhirlpoolFees<'info> {
+    #[account(mut,
+        has_one = whirlpool_position,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            whirlpool.key().as_ref()
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ----------------
+    // Whirlpool accounts
+    #[account(mut)]
+    pub whirlpool: Box<Account<'info, Whirlpool>>,
+    #[account(mut,
+        address = whirlpool.token_vault_a.key()
+    )]
+    pub whirlpool_base_token_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = whirlpool.token_vault_b.key()
+    )]
+    pub whirlpool_quote_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, has_one = whirlpool)]
+    pub whirlpool_position: Box<Account<'info, WhirlpoolPosition>>,
+    #[account(
+        constraint = whirlpool_position_token_account.amount == 1,
+        associated_token::mint = whirlpool_position.position_mint,
+        associated_token::authority = vault,
+    )]
+    pub whirlpool_position_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_lower,
+            &whirlpool.key(),
+            whirlpool_position.tick_lower_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayLower,
+    )]
+    pub whirlpool_position_tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_upper,
+            &whirlpool.key(),
+            whirlpool_position.tick_upper_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayUpper,
+    )]
+    pub whirlpool_position_tick_array_upper: AccountLoader<'info, TickArray>,
+
+    pub whirlpool_program: Program<'info, WhirlpoolProgram>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> HarvestWhirlpoolFees<'info> {
+    pub fn get_update_fees_and_rewards_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, UpdateFeesAndRewards<'info>> {
+        let update_fees_and_rewards_accounts = UpdateFeesAndRewards {
+            whirlpool: self.whirlpool.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            tick_array_lower: self.whirlpool_position_tick_array_lower.to_account_info(),
+            tick_array_upper: self.whirlpool_position_tick_array_upper.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            update_fees_and_rewards_accounts,
+        )
+    }
+
+    pub fn get_collect_fees_context(&self) -> CpiContext<'_, '_, '_, 'info, CollectFees<'info>> {
+        let collect_fees_accounts = CollectFees {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.vault.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            position_token_account: self.whirlpool_position_token_account.to_account_info(),
+            token_owner_account_a: self.vault_base_token_account.to_account_info(),
+            token_vault_a: self.whirlpool_base_token_vault.to_account_info(),
+            token_owner_account_b: self.vault_quote_token_account.to_account_info(),
+            token_vault_b: self.whirlpool_quote_token_vault.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            collect_fees_accounts,
+        )
+    }
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use crate::events::AdminConfigInitialized;
 use crate::state::AdminConfig;
 use crate::Program;
 
@@ -9,7 +10,10 @@ pub fn handler(ctx: Context<InitializeAdminConfig>) -> Result<()> {
     ctx.accounts.admin_config.set_inner(AdminConfig {
         bump: *admin_config_bump,
         admin_key,
+        treasury: admin_key,
+        drift_keeper: Pubkey::default(),
     });
+    emit!(AdminConfigInitialized { admin: admin_key });
     Ok(())
 }
 
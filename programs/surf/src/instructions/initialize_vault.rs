@@ -8,12 +8,13 @@ use drift::{
     cpi as drift_cpi,
     cpi::accounts::{InitializeUser, InitializeUserStats},
     program::Drift as DriftProgram,
-    state::state::State,
+    state::{spot_market::SpotMarket, state::State},
 };
 use whirlpools::{program::Whirlpool as WhirlpoolProgram, state::Whirlpool};
 
 use crate::{
     errors::SurfError,
+    events::VaultInitialized,
     state::{AdminConfig, Vault},
     utils::constraints::is_admin,
 };
@@ -73,6 +74,8 @@ pub fn handler(
 
     // --------
     // Initialize vault
+    let drift_base_spot_market_index = ctx.accounts.drift_base_spot_market.load()?.market_index;
+    let drift_quote_spot_market_index = ctx.accounts.drift_quote_spot_market.load()?.market_index;
     ctx.accounts.vault.initialize(
         *vault_bump,
         ctx.accounts.whirlpool.key(),
@@ -80,17 +83,32 @@ pub fn handler(
         ctx.accounts.quote_token_mint.key(),
         ctx.accounts.vault_base_token_account.key(),
         ctx.accounts.vault_quote_token_account.key(),
+        ctx.accounts.share_mint.key(),
         ctx.accounts.drift_stats.key(),
         ctx.accounts.drift_subaccount.key(),
+        drift_base_spot_market_index,
+        drift_quote_spot_market_index,
         full_tick_range,
         vault_tick_range,
         hedge_tick_range,
+        Clock::get()?.unix_timestamp,
     );
 
+    emit!(VaultInitialized {
+        vault: ctx.accounts.vault.key(),
+        whirlpool: ctx.accounts.whirlpool.key(),
+        base_token_mint: ctx.accounts.base_token_mint.key(),
+        quote_token_mint: ctx.accounts.quote_token_mint.key(),
+        share_mint: ctx.accounts.share_mint.key(),
+        drift_subaccount: ctx.accounts.drift_subaccount.key(),
+        full_tick_range,
+        vault_tick_range,
+        hedge_tick_range,
+    });
+
     Ok(())
 }
 
-// TODO: Check if token mints correspond with vault token mints in subsequent ixs
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
     #[account(mut)]
@@ -143,6 +161,19 @@ pub struct InitializeVault<'info> {
     )]
     pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
 
+    // Shares are minted 1:1 with deposited quote value for the first deposit
+    #[account(init,
+        payer = admin,
+        seeds = [
+            Vault::SHARE_MINT_NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = quote_token_mint.decimals,
+        mint::authority = vault,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
     // ------
     // Drift accounts
     /// CHECK: Drift program validates the account in the CPI
@@ -171,6 +202,15 @@ pub struct InitializeVault<'info> {
     #[account(mut)]
     pub drift_state: Box<Account<'info, State>>,
 
+    #[account(
+        constraint = drift_base_spot_market.load()?.mint.eq(&base_token_mint.key()) @SurfError::InvalidDriftBaseSpotMarketMint,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        constraint = drift_quote_spot_market.load()?.mint.eq(&quote_token_mint.key()) @SurfError::InvalidDriftQuoteSpotMarketMint,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+
     pub whirlpool_program: Program<'info, WhirlpoolProgram>,
     pub drift_program: Program<'info, DriftProgram>,
     pub system_program: Program<'info, System>,
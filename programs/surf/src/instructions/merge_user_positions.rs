@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    errors::SurfError,
+    events::UserPositionsMerged,
+    state::{UserPosition, Vault},
+};
+
+// Moves all shares into existing position of destination owner
+pub fn handler(ctx: Context<MergeUserPositions>) -> Result<()> {
+    let user_position = &mut ctx.accounts.user_position;
+    let destination_user_position = &mut ctx.accounts.destination_user_position;
+
+    user_position.sync_shares(ctx.accounts.owner_share_token_account.amount);
+    destination_user_position.sync_shares(ctx.accounts.destination_share_token_account.amount);
+
+    let shares = user_position.shares;
+    user_position.transfer_shares(destination_user_position, shares)?;
+
+    if shares > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_share_token_account.to_account_info(),
+                    to: ctx
+                        .accounts
+                        .destination_share_token_account
+                        .to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+    }
+
+    emit!(UserPositionsMerged {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.owner.key(),
+        destination_owner: ctx.accounts.destination_user_position.owner,
+        shares,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MergeUserPositions<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        has_one = vault,
+        has_one = owner,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub owner_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        has_one = vault,
+        constraint = destination_user_position.owner.ne(&owner.key()) @SurfError::SameUserPositionOwner,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            destination_user_position.owner.as_ref(),
+        ],
+        bump = destination_user_position.bump,
+    )]
+    pub destination_user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = destination_user_position.owner,
+    )]
+    pub destination_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
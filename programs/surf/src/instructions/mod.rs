@@ -1,9 +1,75 @@
+pub mod add_depositor_allowlist_entry;
+pub mod claim_withdraw;
+pub mod close_user_position;
+pub mod close_vault;
+pub mod collect_protocol_fees;
 pub mod deposit;
+pub mod emergency_deleverage;
+pub mod harvest_whirlpool_fees;
 pub mod initialize_admin_config;
 pub mod initialize_vault;
+pub mod merge_user_positions;
 pub mod open_whirlpool_position;
+pub mod rebalance_drift_collateral;
+pub mod remove_depositor_allowlist_entry;
+pub mod request_withdraw;
+pub mod resolve_vault_recovery;
+pub mod set_protocol_drift_keeper;
+pub mod set_protocol_treasury;
+pub mod set_vault_access_mode;
+pub mod set_vault_borrow_limits;
+pub mod set_vault_deleverage_config;
+pub mod set_vault_deposit_caps;
+pub mod set_vault_drift_delegate;
+pub mod set_vault_fees;
+pub mod set_vault_hedge_mode;
+pub mod set_vault_hedge_mode_switch;
+pub mod set_vault_hedge_ratio;
+pub mod set_vault_min_health_ratio;
+pub mod set_vault_target_collateral_ratio;
+pub mod set_vault_withdraw_cooldown;
+pub mod split_user_position;
+pub mod switch_vault_hedge_mode;
+pub mod sync_vault_idle_token_amounts;
+pub mod transfer_user_position;
+pub mod unwind_vault;
+pub mod update_vault_status;
+pub mod withdraw;
 
+pub use add_depositor_allowlist_entry::*;
+pub use claim_withdraw::*;
+pub use close_user_position::*;
+pub use close_vault::*;
+pub use collect_protocol_fees::*;
 pub use deposit::*;
+pub use emergency_deleverage::*;
+pub use harvest_whirlpool_fees::*;
 pub use initialize_admin_config::*;
 pub use initialize_vault::*;
+pub use merge_user_positions::*;
 pub use open_whirlpool_position::*;
+pub use rebalance_drift_collateral::*;
+pub use remove_depositor_allowlist_entry::*;
+pub use request_withdraw::*;
+pub use resolve_vault_recovery::*;
+pub use set_protocol_drift_keeper::*;
+pub use set_protocol_treasury::*;
+pub use set_vault_access_mode::*;
+pub use set_vault_borrow_limits::*;
+pub use set_vault_deleverage_config::*;
+pub use set_vault_deposit_caps::*;
+pub use set_vault_drift_delegate::*;
+pub use set_vault_fees::*;
+pub use set_vault_hedge_mode::*;
+pub use set_vault_hedge_mode_switch::*;
+pub use set_vault_hedge_ratio::*;
+pub use set_vault_min_health_ratio::*;
+pub use set_vault_target_collateral_ratio::*;
+pub use set_vault_withdraw_cooldown::*;
+pub use split_user_position::*;
+pub use switch_vault_hedge_mode::*;
+pub use sync_vault_idle_token_amounts::*;
+pub use transfer_user_position::*;
+pub use unwind_vault::*;
+pub use update_vault_status::*;
+pub use withdraw::*;
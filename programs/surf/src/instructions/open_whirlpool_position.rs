@@ -9,6 +9,7 @@ use whirlpools::{
 
 use crate::{
     errors::SurfError,
+    events::WhirlpoolPositionOpened,
     state::Vault,
     utils::orca::tick_math::{get_initializable_tick_index, MAX_TICK_INDEX, MIN_TICK_INDEX},
 };
@@ -64,6 +65,17 @@ pub fn handler(
         .vault
         .open_position(current_tick_index, ctx.accounts.whirlpool_position.key());
 
+    let vault = &ctx.accounts.vault;
+    emit!(WhirlpoolPositionOpened {
+        vault: vault.key(),
+        whirlpool_position: vault.whirlpool_position,
+        tick_lower_index: tick_lower_initializable,
+        tick_upper_index: tick_upper_initializable,
+        vault_lower_tick_index: vault.vault_lower_tick_index,
+        vault_upper_tick_index: vault.vault_upper_tick_index,
+        tick_current_index: current_tick_index,
+    });
+
     Ok(())
 }
 
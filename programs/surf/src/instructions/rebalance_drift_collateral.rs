@@ -0,0 +1,272 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use drift::{
+    cpi::{
+        self as drift_cpi,
+        accounts::{Deposit as DriftDeposit, Withdraw as DriftWithdraw},
+    },
+    program::Drift,
+    state::{
+        perp_market::PerpMarket,
+        spot_market::SpotMarket,
+        state::State as DriftState,
+        user::{User as DriftUser, UserStats as DriftUserStats},
+    },
+};
+
+use crate::{
+    errors::SurfError,
+    events::DriftCollateralRebalanced,
+    state::{Vault, VaultStatus},
+    utils::{
+        constraints::{is_drift_perp_market, is_drift_spot_market},
+        drift::{
+            margin::get_health_ratio,
+            oracle::{get_oracle_price, get_quote_value},
+            perp::get_perp_position_value,
+            spot_balance::get_spot_token_amount,
+        },
+    },
+};
+
+// Permissionless, moves quote tokens between idle vault balance and drift collateral
+// so drift collateral matches target collateral ratio of the hedge
+pub fn handler(ctx: Context<RebalanceDriftCollateral>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    if vault.target_collateral_ratio == 0 {
+        return Err(SurfError::CollateralRebalanceDisabled.into());
+    }
+    if vault.status == VaultStatus::Recovery
+        || vault.is_drift_subaccount_liquidated(&*ctx.accounts.drift_subaccount.load()?)
+    {
+        return Err(SurfError::VaultInRecovery.into());
+    }
+    vault.validate_drift_delegate(&*ctx.accounts.drift_subaccount.load()?)?;
+
+    let base_token_oracle_price = ctx.accounts.get_base_token_oracle_price()?;
+    let pre_rebalance_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    let (drift_borrow_amount, drift_collateral_amount, base_token_decimals, perp_notional_value) = {
+        let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+        let (_, perp_notional_value) = get_perp_position_value(
+            &drift_subaccount,
+            &*ctx.accounts.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )?;
+        let drift_base_spot_market = ctx.accounts.drift_base_spot_market.load()?;
+        let drift_base_token_amount =
+            get_spot_token_amount(&drift_subaccount, &drift_base_spot_market)?;
+        let drift_quote_token_amount = get_spot_token_amount(
+            &drift_subaccount,
+            &*ctx.accounts.drift_quote_spot_market.load()?,
+        )?;
+        (
+            u64::try_from(-drift_base_token_amount.min(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            u64::try_from(drift_quote_token_amount.max(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            drift_base_spot_market.decimals,
+            perp_notional_value,
+        )
+    };
+    let drift_borrow_value = get_quote_value(
+        drift_borrow_amount as i128,
+        base_token_oracle_price,
+        base_token_decimals,
+    )?;
+    // Hedge value is the value of base borrow or the notional value of perp short
+    let hedge_value = (drift_borrow_value as u128)
+        .checked_add(perp_notional_value)
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or(SurfError::NetAssetValueOverflow)?;
+
+    let collateral_delta = ctx
+        .accounts
+        .vault
+        .get_drift_collateral_delta(hedge_value, drift_collateral_amount)?;
+    if collateral_delta == 0 {
+        return Err(SurfError::DriftCollateralOnTarget.into());
+    }
+
+    let whirlpool_key = ctx.accounts.vault.whirlpool;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+
+    let quote_token_amount = collateral_delta.unsigned_abs() as u64;
+    if collateral_delta > 0 {
+        drift_cpi::deposit(
+            ctx.accounts.get_drift_deposit_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_quote_spot_market_index,
+            quote_token_amount,
+            false,
+        )?;
+    } else {
+        drift_cpi::withdraw(
+            ctx.accounts.get_drift_withdraw_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_quote_spot_market_index,
+            quote_token_amount,
+            true,
+        )?;
+    }
+
+    let post_rebalance_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    let vault = &mut ctx.accounts.vault;
+    vault.validate_health_ratio(pre_rebalance_health_ratio, post_rebalance_health_ratio)?;
+    vault.update_idle_token_amounts(0, -collateral_delta)?;
+
+    emit!(DriftCollateralRebalanced {
+        vault: vault.key(),
+        drift_borrow_base_token_amount: drift_borrow_amount,
+        drift_collateral_quote_token_amount: drift_collateral_amount,
+        quote_token_amount_delta: collateral_delta,
+        pre_rebalance_health_ratio,
+        post_rebalance_health_ratio,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RebalanceDriftCollateral<'info> {
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ----------------
+    // Drift accounts
+    pub drift_state: Box<Account<'info, DriftState>>,
+    /// CHECK: Address is validated against drift state signer
+    #[account(
+        address = drift_state.signer @SurfError::InvalidDriftSigner,
+    )]
+    pub drift_signer: UncheckedAccount<'info>,
+    #[account(mut,
+        address = drift_quote_spot_market.load()?.vault @SurfError::InvalidDriftQuoteSpotMarketVault,
+    )]
+    pub drift_quote_spot_market_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Address is validated against drift base spot market oracle
+    #[account(
+        address = drift_base_spot_market.load()?.oracle @SurfError::InvalidDriftBaseTokenOracle,
+    )]
+    pub drift_base_token_oracle: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_quote_spot_market,
+            vault.drift_quote_spot_market_index,
+        ) @SurfError::InvalidDriftQuoteSpotMarket,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_perp_market(
+            &drift_perp_market,
+            vault.drift_perp_market_index,
+        ) @SurfError::InvalidDriftPerpMarket,
+    )]
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
+
+    #[account(mut,
+        address = vault.drift_stats.key(),
+    )]
+    pub drift_stats: AccountLoader<'info, DriftUserStats>,
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RebalanceDriftCollateral<'info> {
+    pub fn get_base_token_oracle_price(&self) -> Result<i64> {
+        let drift_base_spot_market = self.drift_base_spot_market.load()?;
+        get_oracle_price(
+            &self.drift_base_token_oracle.to_account_info(),
+            &drift_base_spot_market,
+            &self.drift_state.oracle_guard_rails,
+            Clock::get()?.slot,
+        )
+    }
+
+    pub fn get_health_ratio(&self, base_token_oracle_price: i64) -> Result<u64> {
+        get_health_ratio(
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
+    // Drift expects oracles first, then spot markets and perp markets
+    pub fn get_drift_remaining_accounts(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.drift_base_token_oracle.to_account_info(),
+            self.drift_quote_spot_market.to_account_info(),
+            self.drift_base_spot_market.to_account_info(),
+            self.drift_perp_market.to_account_info(),
+        ]
+    }
+
+    pub fn get_drift_deposit_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftDeposit<'info>> {
+        let deposit_accounts = DriftDeposit {
+            state: self.drift_state.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_quote_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: deposit_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_withdraw_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftWithdraw<'info>> {
+        let withdraw_accounts = DriftWithdraw {
+            state: self.drift_state.to_account_info(),
+            drift_signer: self.drift_signer.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_quote_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: withdraw_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+}
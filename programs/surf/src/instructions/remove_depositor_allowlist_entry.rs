@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::DepositorAllowlistEntryRemoved,
+    state::{AdminConfig, DepositorAllowlistEntry, Vault},
+    utils::constraints::is_admin,
+};
+
+// Removed depositor can still withdraw
+pub fn handler(ctx: Context<RemoveDepositorAllowlistEntry>) -> Result<()> {
+    emit!(DepositorAllowlistEntryRemoved {
+        vault: ctx.accounts.vault.key(),
+        depositor: ctx.accounts.depositor_allowlist_entry.depositor,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveDepositorAllowlistEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut,
+        close = admin,
+        has_one = vault,
+        seeds = [
+            DepositorAllowlistEntry::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            depositor_allowlist_entry.depositor.as_ref(),
+        ],
+        bump = depositor_allowlist_entry.bump,
+    )]
+    pub depositor_allowlist_entry: Account<'info, DepositorAllowlistEntry>,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    errors::SurfError,
+    events::WithdrawRequested,
+    state::{UserPosition, Vault, WithdrawRequest},
+};
+
+// Locks shares until vault withdraw cooldown elapses, shares are redeemed by claim_withdraw
+pub fn handler(ctx: Context<RequestWithdraw>, shares: u64) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    if vault.withdraw_cooldown_seconds == 0 {
+        return Err(SurfError::WithdrawCooldownNotSet.into());
+    }
+    if shares == 0 {
+        return Err(SurfError::SharesZero.into());
+    }
+    if shares > ctx.accounts.owner_share_token_account.amount {
+        return Err(SurfError::InsufficientShares.into());
+    }
+
+    let user_position = &mut ctx.accounts.user_position;
+    // Shares received by token transfer are redeemable without prior user position
+    if user_position.vault.eq(&Pubkey::default()) {
+        user_position.initialize(
+            *ctx.bumps.get("user_position").unwrap(),
+            vault.key(),
+            ctx.accounts.owner.key(),
+        );
+    }
+    user_position.sync_shares(ctx.accounts.owner_share_token_account.amount);
+    user_position.withdraw(shares)?;
+
+    let claimable_timestamp = Clock::get()?.unix_timestamp + vault.withdraw_cooldown_seconds as i64;
+    ctx.accounts.withdraw_request.set_inner(WithdrawRequest {
+        bump: *ctx.bumps.get("withdraw_request").unwrap(),
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        shares,
+        claimable_timestamp,
+    });
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_share_token_account.to_account_info(),
+                to: ctx
+                    .accounts
+                    .withdraw_request_share_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    emit!(WithdrawRequested {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.owner.key(),
+        shares,
+        claimable_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        payer = owner,
+        space = UserPosition::LEN,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub owner_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(init,
+        payer = owner,
+        space = WithdrawRequest::LEN,
+        seeds = [
+            WithdrawRequest::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub withdraw_request: Box<Account<'info, WithdrawRequest>>,
+    #[account(init,
+        payer = owner,
+        associated_token::mint = share_mint,
+        associated_token::authority = withdraw_request,
+    )]
+    pub withdraw_request_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
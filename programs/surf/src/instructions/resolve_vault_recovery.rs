@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use drift::state::user::User as DriftUser;
+
+use crate::{
+    errors::SurfError,
+    events::VaultRecoveryResolved,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Reopens deposits once drift subaccount is no longer being liquidated
+pub fn handler(ctx: Context<ResolveVaultRecovery>) -> Result<()> {
+    let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.resolve_recovery(&drift_subaccount)?;
+
+    emit!(VaultRecoveryResolved {
+        vault: vault.key(),
+        drift_next_liquidation_id: vault.drift_next_liquidation_id,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResolveVaultRecovery<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError, events::ProtocolDriftKeeperUpdated, state::AdminConfig,
+    utils::constraints::is_admin,
+};
+
+// Vault delegates set to previous keeper stay until they are revoked per vault
+pub fn handler(ctx: Context<SetProtocolDriftKeeper>, drift_keeper: Pubkey) -> Result<()> {
+    ctx.accounts.admin_config.drift_keeper = drift_keeper;
+    emit!(ProtocolDriftKeeperUpdated { drift_keeper });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolDriftKeeper<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+}
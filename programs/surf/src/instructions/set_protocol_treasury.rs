@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError, events::ProtocolTreasuryUpdated, state::AdminConfig,
+    utils::constraints::is_admin,
+};
+
+pub fn handler(ctx: Context<SetProtocolTreasury>, treasury: Pubkey) -> Result<()> {
+    ctx.accounts.admin_config.treasury = treasury;
+    emit!(ProtocolTreasuryUpdated { treasury });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+}
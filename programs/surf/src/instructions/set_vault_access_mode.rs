@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultAccessModeUpdated,
+    state::{AdminConfig, Vault, VaultAccessMode},
+    utils::constraints::is_admin,
+};
+
+pub fn handler(ctx: Context<SetVaultAccessMode>, access_mode: VaultAccessMode) -> Result<()> {
+    ctx.accounts.vault.access_mode = access_mode;
+
+    emit!(VaultAccessModeUpdated {
+        vault: ctx.accounts.vault.key(),
+        access_mode,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultAccessMode<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
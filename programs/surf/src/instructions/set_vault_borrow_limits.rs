@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultBorrowLimitsUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Borrow rate in SPOT_RATE_PRECISION, utilization in SPOT_UTILIZATION_PRECISION, zero means no limit
+pub fn handler(
+    ctx: Context<SetVaultBorrowLimits>,
+    max_borrow_rate: u32,
+    max_utilization: u32,
+) -> Result<()> {
+    ctx.accounts
+        .vault
+        .set_borrow_limits(max_borrow_rate, max_utilization)?;
+
+    emit!(VaultBorrowLimitsUpdated {
+        vault: ctx.accounts.vault.key(),
+        max_borrow_rate,
+        max_utilization,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultBorrowLimits<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultDeleverageConfigUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Health ratios are in HEALTH_RATIO_PRECISION, zero deleverage health ratio disables deleverage
+pub fn handler(
+    ctx: Context<SetVaultDeleverageConfig>,
+    deleverage_health_ratio: u64,
+    deleverage_target_health_ratio: u64,
+    deleverage_bounty_quote_amount: u64,
+) -> Result<()> {
+    ctx.accounts.vault.set_deleverage_config(
+        deleverage_health_ratio,
+        deleverage_target_health_ratio,
+        deleverage_bounty_quote_amount,
+    )?;
+
+    emit!(VaultDeleverageConfigUpdated {
+        vault: ctx.accounts.vault.key(),
+        deleverage_health_ratio,
+        deleverage_target_health_ratio,
+        deleverage_bounty_quote_amount,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultDeleverageConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
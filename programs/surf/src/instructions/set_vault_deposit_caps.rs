@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultDepositCapsUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Caps are denominated in quote token, zero lifts the cap
+pub fn handler(
+    ctx: Context<SetVaultDepositCaps>,
+    max_total_deposit: u64,
+    max_deposit_per_user: u64,
+) -> Result<()> {
+    ctx.accounts
+        .vault
+        .set_deposit_caps(max_total_deposit, max_deposit_per_user);
+
+    emit!(VaultDepositCapsUpdated {
+        vault: ctx.accounts.vault.key(),
+        max_total_deposit,
+        max_deposit_per_user,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultDepositCaps<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
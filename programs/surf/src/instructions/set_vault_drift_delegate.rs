@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use drift::{
+    cpi as drift_cpi, cpi::accounts::UpdateUser, program::Drift, state::user::User as DriftUser,
+};
+
+use crate::{
+    errors::SurfError,
+    events::VaultDriftDelegateUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Default pubkey revokes the delegate, any other delegate has to be protocol drift keeper
+// Drift delegate can only place and cancel orders, withdrawals keep requiring vault signature
+// Collateral withdrawals signed by vault are refused if subaccount delegate differs from vault delegate
+pub fn handler(ctx: Context<SetVaultDriftDelegate>, delegate: Pubkey) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let is_revoked = delegate.eq(&Pubkey::default());
+    if (!is_revoked && delegate.ne(&ctx.accounts.admin_config.drift_keeper))
+        || delegate.eq(&vault_key)
+    {
+        return Err(SurfError::InvalidDriftDelegate.into());
+    }
+
+    let sub_account_id = ctx.accounts.drift_subaccount.load()?.sub_account_id;
+    let whirlpool_key = ctx.accounts.vault.whirlpool;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+    drift_cpi::update_user_delegate(
+        ctx.accounts
+            .get_drift_update_user_context()
+            .with_signer(vault_signer_seeds),
+        sub_account_id,
+        delegate,
+    )?;
+
+    ctx.accounts.vault.drift_delegate = delegate;
+    ctx.accounts
+        .vault
+        .validate_drift_delegate(&*ctx.accounts.drift_subaccount.load()?)?;
+
+    emit!(VaultDriftDelegateUpdated {
+        vault: vault_key,
+        drift_subaccount: ctx.accounts.drift_subaccount.key(),
+        delegate,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultDriftDelegate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+}
+
+impl<'info> SetVaultDriftDelegate<'info> {
+    pub fn get_drift_update_user_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, UpdateUser<'info>> {
+        let update_user_accounts = UpdateUser {
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext::new(self.drift_program.to_account_info(), update_user_accounts)
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::{ManagementFeeAccrued, VaultFeesUpdated},
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+pub fn handler(
+    ctx: Context<SetVaultFees>,
+    performance_fee_bps: u16,
+    management_fee_bps: u16,
+    entry_fee_bps: u16,
+    exit_fee_bps: u16,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    // Management fee up to now is accrued with previous rate
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let management_fee_shares = vault.accrue_management_fee(current_timestamp)?;
+    if management_fee_shares > 0 {
+        emit!(ManagementFeeAccrued {
+            vault: vault.key(),
+            fee_shares: management_fee_shares,
+            timestamp: current_timestamp,
+        });
+    }
+
+    vault.set_fees(
+        performance_fee_bps,
+        management_fee_bps,
+        entry_fee_bps,
+        exit_fee_bps,
+    )?;
+
+    emit!(VaultFeesUpdated {
+        vault: vault.key(),
+        performance_fee_bps,
+        management_fee_bps,
+        entry_fee_bps,
+        exit_fee_bps,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
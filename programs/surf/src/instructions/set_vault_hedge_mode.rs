@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use drift::state::{perp_market::PerpMarket, spot_market::SpotMarket, user::User as DriftUser};
+
+use crate::{
+    errors::SurfError,
+    events::VaultHedgeModeUpdated,
+    state::{AdminConfig, Vault, VaultHedgeMode},
+    utils::{
+        constraints::{is_admin, is_drift_spot_market},
+        drift::spot_balance::get_spot_token_amount,
+    },
+};
+
+// Perp market is priced with base token oracle, so both markets have to share it
+pub fn handler(ctx: Context<SetVaultHedgeMode>, hedge_mode: VaultHedgeMode) -> Result<()> {
+    let drift_perp_market = ctx.accounts.drift_perp_market.load()?;
+    if drift_perp_market
+        .amm
+        .oracle
+        .ne(&ctx.accounts.drift_base_spot_market.load()?.oracle)
+    {
+        return Err(SurfError::InvalidDriftPerpMarketOracle.into());
+    }
+    let drift_perp_market_index = drift_perp_market.market_index;
+
+    // Hedge is read from current vault perp market, not from the one being set
+    let (drift_borrow_amount, drift_perp_base_asset_amount) = {
+        let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+        let drift_base_token_amount = get_spot_token_amount(
+            &drift_subaccount,
+            &*ctx.accounts.drift_base_spot_market.load()?,
+        )?;
+        (
+            u64::try_from(-drift_base_token_amount.min(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            drift_subaccount
+                .perp_positions
+                .iter()
+                .find(|perp_position| {
+                    perp_position.market_index == ctx.accounts.vault.drift_perp_market_index
+                })
+                .map_or(0, |perp_position| perp_position.base_asset_amount),
+        )
+    };
+
+    let vault = &mut ctx.accounts.vault;
+    vault.set_hedge_mode(
+        hedge_mode,
+        drift_perp_market_index,
+        drift_borrow_amount,
+        drift_perp_base_asset_amount,
+    )?;
+
+    emit!(VaultHedgeModeUpdated {
+        vault: vault.key(),
+        hedge_mode,
+        drift_perp_market_index,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultHedgeMode<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultHedgeModeSwitchUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Threshold is an annual rate in SPOT_RATE_PRECISION
+pub fn handler(
+    ctx: Context<SetVaultHedgeModeSwitch>,
+    hedge_mode_switch_enabled: bool,
+    hedge_mode_switch_threshold_rate: u32,
+) -> Result<()> {
+    ctx.accounts
+        .vault
+        .set_hedge_mode_switch(hedge_mode_switch_enabled, hedge_mode_switch_threshold_rate);
+
+    emit!(VaultHedgeModeSwitchUpdated {
+        vault: ctx.accounts.vault.key(),
+        hedge_mode_switch_enabled,
+        hedge_mode_switch_threshold_rate,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultHedgeModeSwitch<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
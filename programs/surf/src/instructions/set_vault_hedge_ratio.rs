@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultHedgeRatioUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Applies to new deposits, existing drift borrow is repaid pro-rata on withdraw
+pub fn handler(ctx: Context<SetVaultHedgeRatio>, hedge_ratio_bps: u16) -> Result<()> {
+    ctx.accounts.vault.set_hedge_ratio(hedge_ratio_bps)?;
+
+    emit!(VaultHedgeRatioUpdated {
+        vault: ctx.accounts.vault.key(),
+        hedge_ratio_bps,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultHedgeRatio<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
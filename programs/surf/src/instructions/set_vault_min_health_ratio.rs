@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultMinHealthRatioUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Health ratio is in HEALTH_RATIO_PRECISION, zero disables the check
+pub fn handler(ctx: Context<SetVaultMinHealthRatio>, min_health_ratio: u64) -> Result<()> {
+    ctx.accounts.vault.min_health_ratio = min_health_ratio;
+
+    emit!(VaultMinHealthRatioUpdated {
+        vault: ctx.accounts.vault.key(),
+        min_health_ratio,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultMinHealthRatio<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
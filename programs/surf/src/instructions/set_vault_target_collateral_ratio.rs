@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultTargetCollateralRatioUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Ratio is in BPS_DENOMINATOR, zero disables drift collateral rebalance
+pub fn handler(
+    ctx: Context<SetVaultTargetCollateralRatio>,
+    target_collateral_ratio: u64,
+) -> Result<()> {
+    ctx.accounts
+        .vault
+        .set_target_collateral_ratio(target_collateral_ratio)?;
+
+    emit!(VaultTargetCollateralRatioUpdated {
+        vault: ctx.accounts.vault.key(),
+        target_collateral_ratio,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultTargetCollateralRatio<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SurfError,
+    events::VaultWithdrawCooldownUpdated,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Zero cooldown keeps instant withdraw, pending withdraw requests stay claimable
+pub fn handler(
+    ctx: Context<SetVaultWithdrawCooldown>,
+    withdraw_cooldown_seconds: u32,
+) -> Result<()> {
+    ctx.accounts.vault.withdraw_cooldown_seconds = withdraw_cooldown_seconds;
+
+    emit!(VaultWithdrawCooldownUpdated {
+        vault: ctx.accounts.vault.key(),
+        withdraw_cooldown_seconds,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultWithdrawCooldown<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
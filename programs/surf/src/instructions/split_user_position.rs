@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    errors::SurfError,
+    events::UserPositionSplit,
+    state::{UserPosition, Vault},
+};
+
+// Moves part of shares to position of destination owner
+pub fn handler(ctx: Context<SplitUserPosition>, shares: u64) -> Result<()> {
+    if shares == 0 {
+        return Err(SurfError::SharesZero.into());
+    }
+
+    let vault = &ctx.accounts.vault;
+    let user_position = &mut ctx.accounts.user_position;
+    let destination_user_position = &mut ctx.accounts.destination_user_position;
+
+    if destination_user_position.vault.eq(&Pubkey::default()) {
+        destination_user_position.initialize(
+            *ctx.bumps.get("destination_user_position").unwrap(),
+            vault.key(),
+            ctx.accounts.destination_owner.key(),
+        );
+    }
+
+    user_position.sync_shares(ctx.accounts.owner_share_token_account.amount);
+    destination_user_position.sync_shares(ctx.accounts.destination_share_token_account.amount);
+
+    user_position.transfer_shares(destination_user_position, shares)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_share_token_account.to_account_info(),
+                to: ctx
+                    .accounts
+                    .destination_share_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    emit!(UserPositionSplit {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.owner.key(),
+        destination_owner: ctx.accounts.destination_owner.key(),
+        shares,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SplitUserPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: Any wallet can receive part of user position
+    #[account(
+        constraint = destination_owner.key().ne(&owner.key()) @SurfError::SameUserPositionOwner,
+    )]
+    pub destination_owner: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        has_one = vault,
+        has_one = owner,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub owner_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(init_if_needed,
+        payer = owner,
+        space = UserPosition::LEN,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault.key().as_ref(),
+            destination_owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub destination_user_position: Box<Account<'info, UserPosition>>,
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = share_mint,
+        associated_token::authority = destination_owner,
+    )]
+    pub destination_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,438 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use drift::{
+    controller::position::PositionDirection,
+    cpi::{
+        self as drift_cpi,
+        accounts::{Deposit as DriftDeposit, PlaceAndTake, SettlePNL, Withdraw as DriftWithdraw},
+    },
+    program::Drift,
+    state::{
+        perp_market::PerpMarket,
+        spot_market::SpotMarket,
+        state::State as DriftState,
+        user::{User as DriftUser, UserStats as DriftUserStats},
+    },
+};
+use whirlpools::{Position as WhirlpoolPosition, Whirlpool};
+use whirlpools_client::math::sqrt_price_from_tick_index;
+
+use crate::{
+    errors::SurfError,
+    events::HedgeModeSwitched,
+    state::{Vault, VaultHedgeMode, VaultStatus},
+    utils::{
+        constraints::{is_drift_perp_market, is_drift_spot_market},
+        drift::{
+            margin::get_health_ratio,
+            oracle::{get_oracle_price, get_oracle_sqrt_price},
+            perp::{
+                get_base_token_amount_from_perp, get_perp_base_asset_amount,
+                get_perp_market_order_params, get_perp_order_base_asset_amount,
+            },
+            rates::{
+                get_perp_short_funding_rate, get_spot_market_borrow_conditions,
+                get_spot_market_borrow_rate,
+            },
+            spot_balance::get_spot_token_amount,
+        },
+        orca::position_math::get_token_amounts_from_liquidity,
+    },
+};
+
+// Permissionless, migrates hedge between drift spot borrow and perp short when the other is cheaper
+// Both legs are executed in one transaction, so hedged amount stays within tolerance
+// New instrument hedges at most vault hedge ratio of whirlpool base tokens
+pub fn handler(ctx: Context<SwitchVaultHedgeMode>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    if !vault.hedge_mode_switch_enabled {
+        return Err(SurfError::HedgeModeSwitchDisabled.into());
+    }
+    if vault.status == VaultStatus::Recovery
+        || vault.is_drift_subaccount_liquidated(&*ctx.accounts.drift_subaccount.load()?)
+    {
+        return Err(SurfError::VaultInRecovery.into());
+    }
+
+    let base_token_oracle_price = ctx.accounts.get_base_token_oracle_price()?;
+    let spot_borrow_rate =
+        get_spot_market_borrow_rate(&*ctx.accounts.drift_base_spot_market.load()?)? as i128;
+    let perp_short_funding_rate = get_perp_short_funding_rate(
+        &*ctx.accounts.drift_perp_market.load()?,
+        base_token_oracle_price,
+    )?;
+    if !vault.is_hedge_mode_switch_beneficial(spot_borrow_rate, perp_short_funding_rate) {
+        return Err(SurfError::HedgeModeSwitchNotBeneficial.into());
+    }
+
+    let pre_switch_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    let (pre_drift_base_token_amount, pre_drift_perp_base_asset_amount) =
+        ctx.accounts.get_hedge_amounts()?;
+    let pre_hedge_base_token_amount = ctx.accounts.get_hedge_base_token_amount(
+        pre_drift_base_token_amount,
+        pre_drift_perp_base_asset_amount,
+    )?;
+    let target_hedge_base_token_amount = pre_hedge_base_token_amount.min(
+        ctx.accounts
+            .get_target_hedge_base_token_amount(base_token_oracle_price)?,
+    );
+    let base_token_decimals = ctx.accounts.drift_base_spot_market.load()?.decimals;
+
+    let whirlpool_key = ctx.accounts.vault.whirlpool;
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+
+    match ctx.accounts.vault.hedge_mode {
+        VaultHedgeMode::SpotBorrow => {
+            let drift_borrow_amount = u64::try_from(-pre_drift_base_token_amount.min(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?;
+            let perp_short_base_asset_amount = get_perp_order_base_asset_amount(
+                target_hedge_base_token_amount,
+                base_token_decimals,
+                ctx.accounts.drift_perp_market.load()?.amm.order_step_size,
+            )
+            .ok_or(SurfError::BaseTokenOverflow)?;
+            if perp_short_base_asset_amount > 0 {
+                drift_cpi::place_and_take_perp_order(
+                    ctx.accounts
+                        .get_drift_place_and_take_context(vault_signer_seeds),
+                    get_perp_market_order_params(
+                        PositionDirection::Short,
+                        perp_short_base_asset_amount,
+                        ctx.accounts.vault.drift_perp_market_index,
+                        false,
+                        base_token_oracle_price,
+                        Vault::MAX_SWAP_SLIPPAGE_BPS,
+                    )?,
+                    None,
+                )?;
+            }
+
+            // Borrowed base tokens are held idle by vault
+            let repay_base_amount =
+                drift_borrow_amount.min(ctx.accounts.vault.idle_base_token_amount);
+            if repay_base_amount > 0 {
+                drift_cpi::deposit(
+                    ctx.accounts.get_drift_deposit_context(vault_signer_seeds),
+                    ctx.accounts.vault.drift_base_spot_market_index,
+                    repay_base_amount,
+                    true,
+                )?;
+            }
+            ctx.accounts
+                .vault
+                .update_idle_token_amounts(-(repay_base_amount as i128), 0)?;
+        }
+        VaultHedgeMode::PerpShort => {
+            let perp_short_base_asset_amount =
+                pre_drift_perp_base_asset_amount.min(0).unsigned_abs();
+            let borrow_base_amount = target_hedge_base_token_amount;
+            if borrow_base_amount > 0 {
+                let (utilization, borrow_rate) = get_spot_market_borrow_conditions(
+                    &*ctx.accounts.drift_base_spot_market.load()?,
+                    borrow_base_amount,
+                )?;
+                ctx.accounts
+                    .vault
+                    .validate_borrow_conditions(utilization, borrow_rate)?;
+
+                drift_cpi::withdraw(
+                    ctx.accounts.get_drift_withdraw_context(vault_signer_seeds),
+                    ctx.accounts.vault.drift_base_spot_market_index,
+                    borrow_base_amount,
+                    false,
+                )?;
+            }
+            if perp_short_base_asset_amount > 0 {
+                drift_cpi::place_and_take_perp_order(
+                    ctx.accounts
+                        .get_drift_place_and_take_context(vault_signer_seeds),
+                    get_perp_market_order_params(
+                        PositionDirection::Long,
+                        perp_short_base_asset_amount,
+                        ctx.accounts.vault.drift_perp_market_index,
+                        true,
+                        base_token_oracle_price,
+                        Vault::MAX_SWAP_SLIPPAGE_BPS,
+                    )?,
+                    None,
+                )?;
+            }
+            if pre_drift_perp_base_asset_amount != 0 {
+                drift_cpi::settle_pnl(
+                    ctx.accounts
+                        .get_drift_settle_pnl_context(vault_signer_seeds),
+                    ctx.accounts.vault.drift_perp_market_index,
+                )?;
+            }
+            ctx.accounts
+                .vault
+                .update_idle_token_amounts(borrow_base_amount as i128, 0)?;
+        }
+    }
+
+    let (post_drift_base_token_amount, post_drift_perp_base_asset_amount) =
+        ctx.accounts.get_hedge_amounts()?;
+    let post_hedge_base_token_amount = ctx.accounts.get_hedge_base_token_amount(
+        post_drift_base_token_amount,
+        post_drift_perp_base_asset_amount,
+    )?;
+
+    let post_switch_health_ratio = ctx.accounts.get_health_ratio(base_token_oracle_price)?;
+    let vault = &mut ctx.accounts.vault;
+    vault.validate_health_ratio(pre_switch_health_ratio, post_switch_health_ratio)?;
+    let hedge_mode =
+        vault.switch_hedge_mode(target_hedge_base_token_amount, post_hedge_base_token_amount)?;
+
+    emit!(HedgeModeSwitched {
+        vault: vault.key(),
+        hedge_mode,
+        spot_borrow_rate,
+        perp_short_funding_rate,
+        pre_hedge_base_token_amount,
+        post_hedge_base_token_amount,
+        drift_base_token_amount_delta: post_drift_base_token_amount - pre_drift_base_token_amount,
+        drift_perp_base_asset_amount_delta: (post_drift_perp_base_asset_amount as i128)
+            - (pre_drift_perp_base_asset_amount as i128),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwitchVaultHedgeMode<'info> {
+    #[account(mut,
+        has_one = whirlpool,
+        has_one = whirlpool_position,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ----------------
+    // Whirlpool accounts
+    pub whirlpool: Box<Account<'info, Whirlpool>>,
+    pub whirlpool_position: Box<Account<'info, WhirlpoolPosition>>,
+
+    // ----------------
+    // Drift accounts
+    pub drift_state: Box<Account<'info, DriftState>>,
+    /// CHECK: Address is validated against drift state signer
+    #[account(
+        address = drift_state.signer @SurfError::InvalidDriftSigner,
+    )]
+    pub drift_signer: UncheckedAccount<'info>,
+    #[account(mut,
+        address = drift_quote_spot_market.load()?.vault @SurfError::InvalidDriftQuoteSpotMarketVault,
+    )]
+    pub drift_quote_spot_market_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = drift_base_spot_market.load()?.vault @SurfError::InvalidDriftBaseSpotMarketVault,
+    )]
+    pub drift_base_spot_market_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Address is validated against drift base spot market oracle
+    #[account(
+        address = drift_base_spot_market.load()?.oracle @SurfError::InvalidDriftBaseTokenOracle,
+    )]
+    pub drift_base_token_oracle: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_quote_spot_market,
+            vault.drift_quote_spot_market_index,
+        ) @SurfError::InvalidDriftQuoteSpotMarket,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_perp_market(
+            &drift_perp_market,
+            vault.drift_perp_market_index,
+        ) @SurfError::InvalidDriftPerpMarket,
+        constraint = drift_perp_market.load()?.amm.oracle.eq(&drift_base_token_oracle.key())
+            @SurfError::InvalidDriftPerpMarketOracle,
+    )]
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
+
+    #[account(mut,
+        address = vault.drift_stats.key(),
+    )]
+    pub drift_stats: AccountLoader<'info, DriftUserStats>,
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SwitchVaultHedgeMode<'info> {
+    pub fn get_base_token_oracle_price(&self) -> Result<i64> {
+        let drift_base_spot_market = self.drift_base_spot_market.load()?;
+        get_oracle_price(
+            &self.drift_base_token_oracle.to_account_info(),
+            &drift_base_spot_market,
+            &self.drift_state.oracle_guard_rails,
+            Clock::get()?.slot,
+        )
+    }
+
+    pub fn get_health_ratio(&self, base_token_oracle_price: i64) -> Result<u64> {
+        get_health_ratio(
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
+    // Drift base token amount and perp base asset amount
+    pub fn get_hedge_amounts(&self) -> Result<(i128, i64)> {
+        let drift_subaccount = self.drift_subaccount.load()?;
+        Ok((
+            get_spot_token_amount(&drift_subaccount, &*self.drift_base_spot_market.load()?)?,
+            get_perp_base_asset_amount(&drift_subaccount, &*self.drift_perp_market.load()?),
+        ))
+    }
+
+    // Hedge ratio of base tokens in vault whirlpool position, split at oracle price
+    pub fn get_target_hedge_base_token_amount(&self, base_token_oracle_price: i64) -> Result<u64> {
+        let oracle_sqrt_price = get_oracle_sqrt_price(
+            base_token_oracle_price,
+            self.drift_base_spot_market.load()?.decimals,
+            self.drift_quote_spot_market.load()?.decimals,
+        )?;
+        let (whirlpool_base_token_amount, _) = get_token_amounts_from_liquidity(
+            self.vault.liquidity,
+            oracle_sqrt_price,
+            sqrt_price_from_tick_index(self.whirlpool_position.tick_lower_index),
+            sqrt_price_from_tick_index(self.whirlpool_position.tick_upper_index),
+            false,
+        )?;
+        self.vault
+            .get_hedge_base_amount(whirlpool_base_token_amount)
+    }
+
+    // Base tokens borrowed plus base tokens shorted on perp market
+    pub fn get_hedge_base_token_amount(
+        &self,
+        drift_base_token_amount: i128,
+        drift_perp_base_asset_amount: i64,
+    ) -> Result<u64> {
+        let drift_borrow_amount = u64::try_from(-drift_base_token_amount.min(0))
+            .map_err(|_| SurfError::DriftSpotBalanceError)?;
+        get_base_token_amount_from_perp(
+            drift_perp_base_asset_amount.min(0).unsigned_abs(),
+            self.drift_base_spot_market.load()?.decimals,
+        )
+        .and_then(|amount| amount.checked_add(drift_borrow_amount))
+        .ok_or(SurfError::BaseTokenOverflow.into())
+    }
+
+    // Drift expects oracles first, then spot markets and perp markets
+    pub fn get_drift_remaining_accounts(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.drift_base_token_oracle.to_account_info(),
+            self.drift_quote_spot_market.to_account_info(),
+            self.drift_base_spot_market.to_account_info(),
+            self.drift_perp_market.to_account_info(),
+        ]
+    }
+
+    pub fn get_drift_deposit_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftDeposit<'info>> {
+        let deposit_accounts = DriftDeposit {
+            state: self.drift_state.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_base_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_base_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: deposit_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_withdraw_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftWithdraw<'info>> {
+        let withdraw_accounts = DriftWithdraw {
+            state: self.drift_state.to_account_info(),
+            drift_signer: self.drift_signer.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_base_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_base_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: withdraw_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_place_and_take_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, PlaceAndTake<'info>> {
+        let place_and_take_accounts = PlaceAndTake {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: place_and_take_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_settle_pnl_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, SettlePNL<'info>> {
+        let settle_pnl_accounts = SettlePNL {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: settle_pnl_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    errors::SurfError,
+    events::VaultIdleTokenAmountsSynced,
+    state::{AdminConfig, Vault},
+    utils::constraints::is_admin,
+};
+
+// Accounts tokens sent directly to vault token accounts into net asset value
+// Accrued protocol fee tokens stay excluded
+pub fn handler(ctx: Context<SyncVaultIdleTokenAmounts>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.idle_base_token_amount = ctx
+        .accounts
+        .vault_base_token_account
+        .amount
+        .checked_sub(vault.protocol_fee_base_token_amount)
+        .ok_or(SurfError::ProtocolFeeExceedsBalance)?;
+    vault.idle_quote_token_amount = ctx
+        .accounts
+        .vault_quote_token_account
+        .amount
+        .checked_sub(vault.protocol_fee_quote_token_amount)
+        .ok_or(SurfError::ProtocolFeeExceedsBalance)?;
+
+    emit!(VaultIdleTokenAmountsSynced {
+        vault: vault.key(),
+        idle_base_token_amount: vault.idle_base_token_amount,
+        idle_quote_token_amount: vault.idle_quote_token_amount,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SyncVaultIdleTokenAmounts<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+}
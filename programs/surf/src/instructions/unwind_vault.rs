@@ -0,0 +1,522 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use drift::{
+    controller::position::PositionDirection,
+    cpi::{
+        self as drift_cpi,
+        accounts::{Deposit as DriftDeposit, PlaceAndTake, SettlePNL, Withdraw as DriftWithdraw},
+    },
+    program::Drift,
+    state::{
+        perp_market::PerpMarket,
+        spot_market::SpotMarket,
+        state::State as DriftState,
+        user::{User as DriftUser, UserStats as DriftUserStats},
+    },
+};
+use whirlpools::{
+    cpi as whirlpool_cpi,
+    cpi::accounts::{ClosePosition, CollectFees, DecreaseLiquidity},
+    program::Whirlpool as WhirlpoolProgram,
+    Position as WhirlpoolPosition, TickArray, Whirlpool,
+};
+
+use crate::{
+    errors::SurfError,
+    events::VaultUnwound,
+    state::{AdminConfig, Vault},
+    utils::{
+        constraints::{
+            is_admin, is_drift_perp_market, is_drift_spot_market, is_position_tick_array,
+        },
+        drift::{
+            oracle::get_oracle_price,
+            perp::{get_perp_base_asset_amount, get_perp_market_order_params},
+            spot_balance::get_spot_token_amount,
+        },
+    },
+};
+
+// Once every user share is burned, liquidity and drift balances backing dead shares are moved
+// to vault token accounts and whirlpool position is closed, so the vault can be closed
+pub fn handler(ctx: Context<UnwindVault>) -> Result<()> {
+    let share_supply = ctx.accounts.share_mint.supply;
+    if share_supply != 0 {
+        return Err(SurfError::VaultSharesNotZero.into());
+    }
+
+    let whirlpool_key = ctx.accounts.whirlpool.key();
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Vault::NAMESPACE.as_ref(),
+        whirlpool_key.as_ref(),
+        &[ctx.accounts.vault.bump],
+    ]];
+
+    // -------
+    // CLOSE WHIRLPOOL POSITION
+    let liquidity = ctx.accounts.whirlpool_position.liquidity;
+    if liquidity > 0 {
+        whirlpool_cpi::decrease_liquidity(
+            ctx.accounts
+                .get_whirlpool_decrease_liquidity_context()
+                .with_signer(vault_signer_seeds),
+            liquidity,
+            0,
+            0,
+        )?;
+    }
+    // Position can be closed only without owed fees
+    whirlpool_cpi::collect_fees(
+        ctx.accounts
+            .get_collect_fees_context()
+            .with_signer(vault_signer_seeds),
+    )?;
+    whirlpool_cpi::close_position(
+        ctx.accounts
+            .get_close_position_context()
+            .with_signer(vault_signer_seeds),
+    )?;
+
+    // -------
+    // REPAY DRIFT BORROW
+    let (drift_borrow_amount, drift_perp_base_asset_amount) = {
+        let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+        let drift_base_token_amount = get_spot_token_amount(
+            &drift_subaccount,
+            &*ctx.accounts.drift_base_spot_market.load()?,
+        )?;
+        (
+            u64::try_from(-drift_base_token_amount.min(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            get_perp_base_asset_amount(&drift_subaccount, &*ctx.accounts.drift_perp_market.load()?),
+        )
+    };
+    // Base tokens missing after price moves up have to be sent to vault base token account
+    if drift_borrow_amount > 0 {
+        drift_cpi::deposit(
+            ctx.accounts.get_drift_deposit_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_base_spot_market_index,
+            drift_borrow_amount,
+            true,
+        )?;
+    }
+
+    // -------
+    // CLOSE DRIFT PERP SHORT
+    // Reduce only order can not flip the position, rounding up closes dust below order step size
+    let perp_close_base_asset_amount = {
+        let order_step_size = ctx
+            .accounts
+            .drift_perp_market
+            .load()?
+            .amm
+            .order_step_size
+            .max(1);
+        let base_asset_amount = drift_perp_base_asset_amount.min(0).unsigned_abs();
+        match base_asset_amount % order_step_size {
+            0 => base_asset_amount,
+            remainder => base_asset_amount
+                .checked_add(order_step_size - remainder)
+                .ok_or(SurfError::BaseTokenOverflow)?,
+        }
+    };
+    if perp_close_base_asset_amount > 0 {
+        let base_token_oracle_price = ctx.accounts.get_base_token_oracle_price()?;
+        drift_cpi::place_and_take_perp_order(
+            ctx.accounts
+                .get_drift_place_and_take_context(vault_signer_seeds),
+            get_perp_market_order_params(
+                PositionDirection::Long,
+                perp_close_base_asset_amount,
+                ctx.accounts.vault.drift_perp_market_index,
+                true,
+                base_token_oracle_price,
+                Vault::MAX_SWAP_SLIPPAGE_BPS,
+            )?,
+            None,
+        )?;
+    }
+    if drift_perp_base_asset_amount != 0 {
+        drift_cpi::settle_pnl(
+            ctx.accounts
+                .get_drift_settle_pnl_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_perp_market_index,
+        )?;
+    }
+
+    // -------
+    // WITHDRAW DRIFT DEPOSITS
+    let (drift_quote_deposit_amount, drift_base_deposit_amount) = {
+        let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+        let drift_quote_token_amount = get_spot_token_amount(
+            &drift_subaccount,
+            &*ctx.accounts.drift_quote_spot_market.load()?,
+        )?;
+        let drift_base_token_amount = get_spot_token_amount(
+            &drift_subaccount,
+            &*ctx.accounts.drift_base_spot_market.load()?,
+        )?;
+        (
+            u64::try_from(drift_quote_token_amount.max(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+            u64::try_from(drift_base_token_amount.max(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?,
+        )
+    };
+    if drift_quote_deposit_amount > 0 {
+        drift_cpi::withdraw(
+            ctx.accounts.get_drift_withdraw_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_quote_spot_market_index,
+            drift_quote_deposit_amount,
+            true,
+        )?;
+    }
+    if drift_base_deposit_amount > 0 {
+        drift_cpi::withdraw(
+            ctx.accounts
+                .get_drift_base_withdraw_context(vault_signer_seeds),
+            ctx.accounts.vault.drift_base_spot_market_index,
+            drift_base_deposit_amount,
+            true,
+        )?;
+    }
+
+    // -------
+    // UPDATE VAULT
+    ctx.accounts.vault_base_token_account.reload()?;
+    ctx.accounts.vault_quote_token_account.reload()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.unwind(share_supply, Clock::get()?.unix_timestamp)?;
+
+    emit!(VaultUnwound {
+        vault: vault.key(),
+        liquidity_delta: liquidity,
+        drift_deposit_base_token_amount: drift_borrow_amount,
+        drift_perp_close_base_asset_amount: perp_close_base_asset_amount,
+        drift_withdraw_quote_token_amount: drift_quote_deposit_amount,
+        drift_withdraw_base_token_amount: drift_base_deposit_amount,
+        base_token_amount: ctx.accounts.vault_base_token_account.amount,
+        quote_token_amount: ctx.accounts.vault_quote_token_account.amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnwindVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = is_admin(&admin_config, &admin) @SurfError::InvalidAdmin,
+        seeds = [AdminConfig::NAMESPACE.as_ref()],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(mut,
+        has_one = whirlpool_position,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            whirlpool.key().as_ref()
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        address = vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ----------------
+    // Whirlpool accounts
+    #[account(mut)]
+    pub whirlpool: Box<Account<'info, Whirlpool>>,
+    #[account(mut,
+        address = whirlpool.token_vault_a.key()
+    )]
+    pub whirlpool_base_token_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = whirlpool.token_vault_b.key()
+    )]
+    pub whirlpool_quote_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, has_one = whirlpool)]
+    pub whirlpool_position: Box<Account<'info, WhirlpoolPosition>>,
+    /// CHECK: Whirlpool program handles checks
+    #[account(mut,
+        address = whirlpool_position.position_mint,
+    )]
+    pub whirlpool_position_mint: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = whirlpool_position_token_account.amount == 1,
+        associated_token::mint = whirlpool_position.position_mint,
+        associated_token::authority = vault,
+    )]
+    pub whirlpool_position_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_lower,
+            &whirlpool.key(),
+            whirlpool_position.tick_lower_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayLower,
+    )]
+    pub whirlpool_position_tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_upper,
+            &whirlpool.key(),
+            whirlpool_position.tick_upper_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayUpper,
+    )]
+    pub whirlpool_position_tick_array_upper: AccountLoader<'info, TickArray>,
+
+    // ----------------
+    // Drift accounts
+    pub drift_state: Box<Account<'info, DriftState>>,
+    /// CHECK: Address is validated against drift state signer
+    #[account(
+        address = drift_state.signer @SurfError::InvalidDriftSigner,
+    )]
+    pub drift_signer: UncheckedAccount<'info>,
+    #[account(mut,
+        address = drift_quote_spot_market.load()?.vault @SurfError::InvalidDriftQuoteSpotMarketVault,
+    )]
+    pub drift_quote_spot_market_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = drift_base_spot_market.load()?.vault @SurfError::InvalidDriftBaseSpotMarketVault,
+    )]
+    pub drift_base_spot_market_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Address is validated against drift base spot market oracle
+    #[account(
+        address = drift_base_spot_market.load()?.oracle @SurfError::InvalidDriftBaseTokenOracle,
+    )]
+    pub drift_base_token_oracle: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_quote_spot_market,
+            vault.drift_quote_spot_market_index,
+        ) @SurfError::InvalidDriftQuoteSpotMarket,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_perp_market(
+            &drift_perp_market,
+            vault.drift_perp_market_index,
+        ) @SurfError::InvalidDriftPerpMarket,
+    )]
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
+
+    #[account(mut,
+        address = vault.drift_stats.key(),
+    )]
+    pub drift_stats: AccountLoader<'info, DriftUserStats>,
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub whirlpool_program: Program<'info, WhirlpoolProgram>,
+    pub drift_program: Program<'info, Drift>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnwindVault<'info> {
+    pub fn get_base_token_oracle_price(&self) -> Result<i64> {
+        let drift_base_spot_market = self.drift_base_spot_market.load()?;
+        get_oracle_price(
+            &self.drift_base_token_oracle.to_account_info(),
+            &drift_base_spot_market,
+            &self.drift_state.oracle_guard_rails,
+            Clock::get()?.slot,
+        )
+    }
+
+    pub fn get_whirlpool_decrease_liquidity_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, DecreaseLiquidity<'info>> {
+        let decrease_liq_accounts = DecreaseLiquidity {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.vault.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            position_token_account: self.whirlpool_position_token_account.to_account_info(),
+            token_owner_account_a: self.vault_base_token_account.to_account_info(),
+            token_owner_account_b: self.vault_quote_token_account.to_account_info(),
+            token_vault_a: self.whirlpool_base_token_vault.to_account_info(),
+            token_vault_b: self.whirlpool_quote_token_vault.to_account_info(),
+            tick_array_lower: self.whirlpool_position_tick_array_lower.to_account_info(),
+            tick_array_upper: self.whirlpool_position_tick_array_upper.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            decrease_liq_accounts,
+        )
+    }
+
+    pub fn get_collect_fees_context(&self) -> CpiContext<'_, '_, '_, 'info, CollectFees<'info>> {
+        let collect_fees_accounts = CollectFees {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.vault.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            position_token_account: self.whirlpool_position_token_account.to_account_info(),
+            token_owner_account_a: self.vault_base_token_account.to_account_info(),
+            token_vault_a: self.whirlpool_base_token_vault.to_account_info(),
+            token_owner_account_b: self.vault_quote_token_account.to_account_info(),
+            token_vault_b: self.whirlpool_quote_token_vault.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            collect_fees_accounts,
+        )
+    }
+
+    // Position rent goes to admin
+    pub fn get_close_position_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, ClosePosition<'info>> {
+        let close_position_accounts = ClosePosition {
+            position_authority: self.vault.to_account_info(),
+            receiver: self.admin.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            position_mint: self.whirlpool_position_mint.to_account_info(),
+            position_token_account: self.whirlpool_position_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            close_position_accounts,
+        )
+    }
+
+    // Drift expects oracles first, then spot markets and perp markets
+    pub fn get_drift_remaining_accounts(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.drift_base_token_oracle.to_account_info(),
+            self.drift_quote_spot_market.to_account_info(),
+            self.drift_base_spot_market.to_account_info(),
+            self.drift_perp_market.to_account_info(),
+        ]
+    }
+
+    pub fn get_drift_deposit_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftDeposit<'info>> {
+        let deposit_accounts = DriftDeposit {
+            state: self.drift_state.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_base_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_base_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: deposit_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_withdraw_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftWithdraw<'info>> {
+        let withdraw_accounts = DriftWithdraw {
+            state: self.drift_state.to_account_info(),
+            drift_signer: self.drift_signer.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_quote_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: withdraw_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_base_withdraw_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftWithdraw<'info>> {
+        let withdraw_accounts = DriftWithdraw {
+            state: self.drift_state.to_account_info(),
+            drift_signer: self.drift_signer.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_base_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_base_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: withdraw_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_place_and_take_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, PlaceAndTake<'info>> {
+        let place_and_take_accounts = PlaceAndTake {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: place_and_take_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_settle_pnl_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, SettlePNL<'info>> {
+        let settle_pnl_accounts = SettlePNL {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: settle_pnl_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+}
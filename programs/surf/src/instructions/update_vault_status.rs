@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use drift::state::user::User as DriftUser;
+
+use crate::{events::VaultRecoveryStarted, state::Vault};
+
+// Permissionless, puts vault into recovery if drift subaccount was liquidated
+pub fn handler(ctx: Context<UpdateVaultStatus>) -> Result<()> {
+    let drift_subaccount = ctx.accounts.drift_subaccount.load()?;
+    let vault = &mut ctx.accounts.vault;
+    if vault.update_status(&drift_subaccount) {
+        emit!(VaultRecoveryStarted {
+            vault: vault.key(),
+            drift_next_liquidation_id: drift_subaccount.next_liquidation_id,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateVaultStatus<'info> {
+    #[account(mut,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            vault.whirlpool.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+}
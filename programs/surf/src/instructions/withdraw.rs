@@ -0,0 +1,959 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use drift::{
+    controller::position::PositionDirection,
+    cpi::{
+        self as drift_cpi,
+        accounts::{Deposit as DriftDeposit, PlaceAndTake, SettlePNL, Withdraw as DriftWithdraw},
+    },
+    program::Drift,
+    state::{
+        perp_market::PerpMarket,
+        spot_market::SpotMarket,
+        state::State as DriftState,
+        user::{User as DriftUser, UserStats as DriftUserStats},
+    },
+};
+use whirlpools::{
+    cpi as whirlpool_cpi,
+    cpi::accounts::{DecreaseLiquidity, Swap},
+    program::Whirlpool as WhirlpoolProgram,
+    Position as WhirlpoolPosition, TickArray, Whirlpool,
+};
+use whirlpools_client::math::{sqrt_price_from_tick_index, MAX_SQRT_PRICE_X64};
+
+use crate::{
+    errors::SurfError,
+    events::{
+        ExitFeeAccrued, ManagementFeeAccrued, VaultHedgeReduced, VaultRecoveryStarted, Withdrawn,
+    },
+    state::{UserPosition, Vault, VaultHedgeMode, VaultStatus},
+    utils::{
+        constraints::{is_drift_perp_market, is_drift_spot_market, is_position_tick_array},
+        drift::{
+            margin::{get_health_ratio, get_margin},
+            oracle::{get_oracle_price, get_oracle_sqrt_price, get_quote_value},
+            perp::{
+                get_base_token_amount_from_perp, get_perp_base_asset_amount,
+                get_perp_market_order_params, get_perp_order_base_asset_amount,
+            },
+            spot_balance::get_spot_token_amount,
+        },
+        math::{mul_div, mul_div_u64, BPS_DENOMINATOR},
+        orca::position_math::get_token_amounts_from_liquidity,
+    },
+};
+
+// Withdraws pro-rata share of every vault asset
+pub fn handler(
+    ctx: Context<Withdraw>,
+    shares: u64,
+    // Minimal token amounts sent to payer, protect against price moves and swap slippage
+    min_base_out: u64,
+    min_quote_out: u64,
+    close_user_position: bool,
+) -> Result<()> {
+    if ctx.accounts.vault_assets.vault.withdraw_cooldown_seconds > 0 {
+        return Err(SurfError::WithdrawCooldownActive.into());
+    }
+    if shares == 0 {
+        return Err(SurfError::SharesZero.into());
+    }
+    if shares > ctx.accounts.payer_share_token_account.amount {
+        return Err(SurfError::InsufficientShares.into());
+    }
+
+    let vault = &ctx.accounts.vault_assets.vault;
+    let user_position = &mut ctx.accounts.user_position;
+    // Shares received by token transfer are redeemable without prior user position
+    if user_position.vault.eq(&Pubkey::default()) {
+        user_position.initialize(
+            *ctx.bumps.get("user_position").unwrap(),
+            vault.key(),
+            ctx.accounts.payer.key(),
+        );
+    }
+    user_position.sync_shares(ctx.accounts.payer_share_token_account.amount);
+    user_position.withdraw(shares)?;
+
+    ctx.accounts.vault_assets.redeem_shares(
+        shares,
+        ctx.accounts.payer.key(),
+        min_base_out,
+        min_quote_out,
+    )?;
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.vault_assets.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.payer_share_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    if close_user_position && ctx.accounts.user_position.is_empty() {
+        ctx.accounts
+            .user_position
+            .close(ctx.accounts.payer.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub vault_assets: WithdrawVaultAssets<'info>,
+
+    #[account(init_if_needed,
+        payer = payer,
+        space = UserPosition::LEN,
+        seeds = [
+            UserPosition::NAMESPACE.as_ref(),
+            vault_assets.vault.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut,
+        address = vault_assets.vault.share_mint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        token::mint = share_mint,
+        token::authority = payer,
+    )]
+    pub payer_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Vault accounts needed to unwind and send pro-rata share of vault assets
+#[derive(Accounts)]
+pub struct WithdrawVaultAssets<'info> {
+    #[account(mut,
+        constraint = payer_base_token_account.mint.eq(&vault.base_token_mint) @SurfError::InvalidPayerBaseTokenMint,
+    )]
+    pub payer_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = payer_quote_token_account.mint.eq(&vault.quote_token_mint) @SurfError::InvalidPayerQuoteTokenMint,
+    )]
+    pub payer_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        has_one = whirlpool_position,
+        seeds = [
+            Vault::NAMESPACE.as_ref(),
+            whirlpool.key().as_ref()
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut,
+        address = vault.base_token_account.key()
+    )]
+    pub vault_base_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = vault.quote_token_account.key()
+    )]
+    pub vault_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ----------------
+    // Whirlpool accounts
+    #[account(mut)]
+    pub whirlpool: Box<Account<'info, Whirlpool>>,
+    #[account(mut,
+        address = whirlpool.token_vault_a.key()
+    )]
+    pub whirlpool_base_token_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = whirlpool.token_vault_b.key()
+    )]
+    pub whirlpool_quote_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, has_one = whirlpool)]
+    pub whirlpool_position: Box<Account<'info, WhirlpoolPosition>>,
+    #[account(mut,
+        constraint = whirlpool_position_token_account.amount == 1,
+        associated_token::mint = whirlpool_position.position_mint,
+        associated_token::authority = vault,
+    )]
+    pub whirlpool_position_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_lower,
+            &whirlpool.key(),
+            whirlpool_position.tick_lower_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayLower,
+    )]
+    pub whirlpool_position_tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(mut,
+        constraint = is_position_tick_array(
+            &whirlpool_position_tick_array_upper,
+            &whirlpool.key(),
+            whirlpool_position.tick_upper_index,
+            whirlpool.tick_spacing,
+        )? @SurfError::InvalidWhirlpoolPositionTickArrayUpper,
+    )]
+    pub whirlpool_position_tick_array_upper: AccountLoader<'info, TickArray>,
+
+    #[account(mut,
+        constraint = swap_tick_array_0.load()?.whirlpool.key().eq(&whirlpool.key())
+    )]
+    pub swap_tick_array_0: AccountLoader<'info, TickArray>,
+    #[account(mut,
+        constraint = swap_tick_array_1.load()?.whirlpool.key().eq(&whirlpool.key())
+    )]
+    pub swap_tick_array_1: AccountLoader<'info, TickArray>,
+    #[account(mut,
+        constraint = swap_tick_array_2.load()?.whirlpool.key().eq(&whirlpool.key())
+    )]
+    pub swap_tick_array_2: AccountLoader<'info, TickArray>,
+
+    /// CHECK: Unused in whirlpools
+    #[account(
+        seeds = [
+            b"oracle".as_ref(),
+            whirlpool.key().as_ref()
+        ],
+        bump,
+        seeds::program = whirlpool_program.key()
+    )]
+    pub swap_oracle: UncheckedAccount<'info>,
+
+    // ----------------
+    // Drift accounts
+    pub drift_state: Box<Account<'info, DriftState>>,
+    /// CHECK: Address is validated against drift state signer
+    #[account(
+        address = drift_state.signer @SurfError::InvalidDriftSigner,
+    )]
+    pub drift_signer: UncheckedAccount<'info>,
+    #[account(mut,
+        address = drift_quote_spot_market.load()?.vault @SurfError::InvalidDriftQuoteSpotMarketVault,
+    )]
+    pub drift_quote_spot_market_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        address = drift_base_spot_market.load()?.vault @SurfError::InvalidDriftBaseSpotMarketVault,
+    )]
+    pub drift_base_spot_market_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Address is validated against drift base spot market oracle
+    #[account(
+        address = drift_base_spot_market.load()?.oracle @SurfError::InvalidDriftBaseTokenOracle,
+    )]
+    pub drift_base_token_oracle: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_base_spot_market,
+            vault.drift_base_spot_market_index,
+        ) @SurfError::InvalidDriftBaseSpotMarket,
+    )]
+    pub drift_base_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_spot_market(
+            &drift_quote_spot_market,
+            vault.drift_quote_spot_market_index,
+        ) @SurfError::InvalidDriftQuoteSpotMarket,
+    )]
+    pub drift_quote_spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut,
+        constraint = is_drift_perp_market(
+            &drift_perp_market,
+            vault.drift_perp_market_index,
+        ) @SurfError::InvalidDriftPerpMarket,
+    )]
+    pub drift_perp_market: AccountLoader<'info, PerpMarket>,
+
+    #[account(mut,
+        address = vault.drift_stats.key(),
+    )]
+    pub drift_stats: AccountLoader<'info, DriftUserStats>,
+    #[account(mut,
+        address = vault.drift_subaccount.key(),
+    )]
+    pub drift_subaccount: AccountLoader<'info, DriftUser>,
+
+    pub whirlpool_program: Program<'info, WhirlpoolProgram>,
+    pub drift_program: Program<'info, Drift>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawVaultAssets<'info> {
+    // Exit fee is taken from redeemed shares, the rest is withdrawn to payer token accounts
+    pub fn redeem_shares(
+        &mut self,
+        shares: u64,
+        owner: Pubkey,
+        min_base_out: u64,
+        min_quote_out: u64,
+    ) -> Result<()> {
+        self.update_vault_status()?;
+        self.vault
+            .validate_drift_delegate(&*self.drift_subaccount.load()?)?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let management_fee_shares = self.vault.accrue_management_fee(current_timestamp)?;
+        if management_fee_shares > 0 {
+            emit!(ManagementFeeAccrued {
+                vault: self.vault.key(),
+                fee_shares: management_fee_shares,
+                timestamp: current_timestamp,
+            });
+        }
+
+        // Exit fee shares are burned from payer and accrued to treasury
+        let exit_fee_shares = Vault::get_fee_shares(shares, self.vault.exit_fee_bps)?;
+        let withdrawn_shares = shares - exit_fee_shares;
+        let total_shares = self.vault.total_shares;
+        let pre_withdraw_vault_base_amount = self.vault_base_token_account.amount;
+        let pre_withdraw_vault_quote_amount = self.vault_quote_token_account.amount;
+
+        // Only accounted idle balances are withdrawn, donations stay in vault
+        let idle_base_amount = mul_div_u64(
+            self.vault.idle_base_token_amount,
+            withdrawn_shares,
+            total_shares,
+            false,
+        )
+        .ok_or(SurfError::BaseTokenOverflow)?;
+        let idle_quote_amount = mul_div_u64(
+            self.vault.idle_quote_token_amount,
+            withdrawn_shares,
+            total_shares,
+            false,
+        )
+        .ok_or(SurfError::QuoteTokenOverflow)?;
+
+        let whirlpool_key = self.whirlpool.key();
+        let vault_bump = self.vault.bump;
+        let vault_signer_seeds: &[&[&[u8]]] = &[&[
+            Vault::NAMESPACE.as_ref(),
+            whirlpool_key.as_ref(),
+            &[vault_bump],
+        ]];
+
+        // -------
+        // WITHDRAW FROM WHIRLPOOL
+        let liquidity = mul_div(
+            self.whirlpool_position.liquidity,
+            withdrawn_shares as u128,
+            total_shares as u128,
+            false,
+        )
+        .ok_or(SurfError::LiquidityOverflow)?;
+
+        if liquidity > 0 {
+            whirlpool_cpi::decrease_liquidity(
+                self.get_whirlpool_decrease_liquidity_context()
+                    .with_signer(vault_signer_seeds),
+                liquidity,
+                0,
+                0,
+            )?;
+            self.vault_base_token_account.reload()?;
+            self.vault_quote_token_account.reload()?;
+        }
+        let whirlpool_base_token_amount = self
+            .vault_base_token_account
+            .amount
+            .checked_sub(pre_withdraw_vault_base_amount)
+            .ok_or(SurfError::BaseTokenOverflow)?;
+        let whirlpool_quote_token_amount = self
+            .vault_quote_token_account
+            .amount
+            .checked_sub(pre_withdraw_vault_quote_amount)
+            .ok_or(SurfError::QuoteTokenUnderflow)?;
+
+        // -------
+        // REPAY DRIFT BORROW
+        let base_token_oracle_price = self.get_base_token_oracle_price()?;
+        let pre_withdraw_health_ratio = self.get_health_ratio(base_token_oracle_price)?;
+        let (drift_borrow_amount, drift_perp_base_asset_amount) = {
+            let drift_subaccount = self.drift_subaccount.load()?;
+            let drift_base_token_amount =
+                get_spot_token_amount(&drift_subaccount, &*self.drift_base_spot_market.load()?)?;
+            (
+                u64::try_from(-drift_base_token_amount.min(0))
+                    .map_err(|_| SurfError::DriftSpotBalanceError)?,
+                get_perp_base_asset_amount(&drift_subaccount, &*self.drift_perp_market.load()?),
+            )
+        };
+
+        let repay_base_amount =
+            mul_div_u64(drift_borrow_amount, withdrawn_shares, total_shares, true)
+                .ok_or(SurfError::BaseTokenOverflow)?;
+        let available_base_amount = self
+            .vault_base_token_account
+            .amount
+            .checked_sub(pre_withdraw_vault_base_amount)
+            .and_then(|amount| amount.checked_add(idle_base_amount))
+            .ok_or(SurfError::BaseTokenOverflow)?;
+
+        // Price moved up, base tokens from whirlpool do not cover the borrow
+        let swap_base_token_amount = repay_base_amount.saturating_sub(available_base_amount);
+        if swap_base_token_amount > 0 {
+            let max_swap_quote_amount =
+                self.get_max_swap_quote_amount(swap_base_token_amount, base_token_oracle_price)?;
+            whirlpool_cpi::swap(
+                self.get_swap_context().with_signer(vault_signer_seeds),
+                swap_base_token_amount,
+                max_swap_quote_amount,
+                MAX_SQRT_PRICE_X64,
+                false,
+                false,
+            )?;
+        }
+
+        if repay_base_amount > 0 {
+            drift_cpi::deposit(
+                self.get_drift_deposit_context(vault_signer_seeds),
+                self.vault.drift_base_spot_market_index,
+                repay_base_amount,
+                true,
+            )?;
+        }
+
+        // -------
+        // CLOSE DRIFT PERP SHORT
+        let perp_close_base_asset_amount = {
+            let order_step_size = self.drift_perp_market.load()?.amm.order_step_size.max(1);
+            let base_asset_amount = mul_div_u64(
+                drift_perp_base_asset_amount.min(0).unsigned_abs(),
+                withdrawn_shares,
+                total_shares,
+                false,
+            )
+            .ok_or(SurfError::BaseTokenOverflow)?;
+            base_asset_amount - base_asset_amount % order_step_size
+        };
+        if perp_close_base_asset_amount > 0 {
+            drift_cpi::place_and_take_perp_order(
+                self.get_drift_place_and_take_context(vault_signer_seeds),
+                get_perp_market_order_params(
+                    PositionDirection::Long,
+                    perp_close_base_asset_amount,
+                    self.vault.drift_perp_market_index,
+                    true,
+                    base_token_oracle_price,
+                    Vault::MAX_SWAP_SLIPPAGE_BPS,
+                )?,
+                None,
+            )?;
+        }
+        // Perp pnl is settled into quote collateral before collateral is withdrawn pro-rata
+        if drift_perp_base_asset_amount != 0 {
+            drift_cpi::settle_pnl(
+                self.get_drift_settle_pnl_context(vault_signer_seeds),
+                self.vault.drift_perp_market_index,
+            )?;
+        }
+
+        // -------
+        // WITHDRAW DRIFT COLLATERAL
+        let drift_collateral_amount = {
+            let drift_quote_token_amount = get_spot_token_amount(
+                &*self.drift_subaccount.load()?,
+                &*self.drift_quote_spot_market.load()?,
+            )?;
+            u64::try_from(drift_quote_token_amount.max(0))
+                .map_err(|_| SurfError::DriftSpotBalanceError)?
+        };
+        let collateral_quote_amount = mul_div_u64(
+            drift_collateral_amount,
+            withdrawn_shares,
+            total_shares,
+            false,
+        )
+        .ok_or(SurfError::QuoteTokenOverflow)?;
+        if collateral_quote_amount > 0 {
+            drift_cpi::withdraw(
+                self.get_drift_withdraw_context(vault_signer_seeds),
+                self.vault.drift_quote_spot_market_index,
+                collateral_quote_amount,
+                true,
+            )?;
+        }
+
+        // Pro-rata withdrawals stay open in recovery regardless of health ratio
+        if self.vault.status != VaultStatus::Recovery {
+            let post_withdraw_health_ratio = self.get_health_ratio(base_token_oracle_price)?;
+            self.vault
+                .validate_health_ratio(pre_withdraw_health_ratio, post_withdraw_health_ratio)?;
+        }
+
+        // -------
+        // TRANSFER TO PAYER
+        self.whirlpool.reload()?;
+        self.vault_base_token_account.reload()?;
+        self.vault_quote_token_account.reload()?;
+
+        let base_amount = (self.vault_base_token_account.amount as i128)
+            - (pre_withdraw_vault_base_amount as i128)
+            + (idle_base_amount as i128);
+        let quote_amount = (self.vault_quote_token_account.amount as i128)
+            - (pre_withdraw_vault_quote_amount as i128)
+            + (idle_quote_amount as i128);
+        let base_amount = u64::try_from(base_amount).map_err(|_| SurfError::BaseTokenOverflow)?;
+        let quote_amount =
+            u64::try_from(quote_amount).map_err(|_| SurfError::QuoteTokenUnderflow)?;
+        if base_amount < min_base_out {
+            return Err(SurfError::WithdrawBaseTokenAmountTooLow.into());
+        }
+        if quote_amount < min_quote_out {
+            return Err(SurfError::WithdrawQuoteTokenAmountTooLow.into());
+        }
+
+        let token_program = &self.token_program;
+        if base_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: self.vault_base_token_account.to_account_info(),
+                        to: self.payer_base_token_account.to_account_info(),
+                        authority: self.vault.to_account_info(),
+                    },
+                    vault_signer_seeds,
+                ),
+                base_amount,
+            )?;
+        }
+        if quote_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: self.vault_quote_token_account.to_account_info(),
+                        to: self.payer_quote_token_account.to_account_info(),
+                        authority: self.vault.to_account_info(),
+                    },
+                    vault_signer_seeds,
+                ),
+                quote_amount,
+            )?;
+        }
+
+        // -------
+        // UPDATE VAULT
+        let vault = &mut self.vault;
+        vault.withdraw(liquidity, shares)?;
+        if exit_fee_shares > 0 {
+            vault.accrue_protocol_fee_shares(exit_fee_shares)?;
+            emit!(ExitFeeAccrued {
+                vault: vault.key(),
+                owner,
+                fee_shares: exit_fee_shares,
+            });
+        }
+        vault
+            .update_idle_token_amounts(-(idle_base_amount as i128), -(idle_quote_amount as i128))?;
+
+        emit!(Withdrawn {
+            vault: vault.key(),
+            owner,
+            shares,
+            exit_fee_shares,
+            liquidity_delta: liquidity,
+            whirlpool_base_token_amount,
+            whirlpool_quote_token_amount,
+            swap_base_token_amount,
+            drift_deposit_base_token_amount: repay_base_amount,
+            drift_perp_close_base_asset_amount: perp_close_base_asset_amount,
+            drift_withdraw_quote_token_amount: collateral_quote_amount,
+            idle_base_token_amount: idle_base_amount,
+            idle_quote_token_amount: idle_quote_amount,
+            base_token_amount: base_amount,
+            quote_token_amount: quote_amount,
+            tick_current_index: self.whirlpool.tick_current_index,
+            vault_liquidity: vault.liquidity,
+            vault_total_shares: vault.total_shares,
+        });
+
+        // Pro-rata unwind keeps previous hedge, so hedge ratio changes are applied here
+        if self.vault.status != VaultStatus::Recovery {
+            self.reduce_hedge_to_ratio(base_token_oracle_price, vault_signer_seeds)?;
+        }
+
+        Ok(())
+    }
+
+    // Reduces drift hedge to vault hedge ratio of remaining whirlpool position split at oracle price
+    // Borrow is repaid with idle base tokens, so net asset value does not change
+    pub fn reduce_hedge_to_ratio(
+        &mut self,
+        base_token_oracle_price: i64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let oracle_sqrt_price = get_oracle_sqrt_price(
+            base_token_oracle_price,
+            self.drift_base_spot_market.load()?.decimals,
+            self.drift_quote_spot_market.load()?.decimals,
+        )?;
+        let (whirlpool_base_token_amount, _) = get_token_amounts_from_liquidity(
+            self.vault.liquidity,
+            oracle_sqrt_price,
+            sqrt_price_from_tick_index(self.whirlpool_position.tick_lower_index),
+            sqrt_price_from_tick_index(self.whirlpool_position.tick_upper_index),
+            false,
+        )?;
+        let (drift_borrow_amount, drift_perp_base_asset_amount) = {
+            let drift_subaccount = self.drift_subaccount.load()?;
+            let drift_base_token_amount =
+                get_spot_token_amount(&drift_subaccount, &*self.drift_base_spot_market.load()?)?;
+            (
+                u64::try_from(-drift_base_token_amount.min(0))
+                    .map_err(|_| SurfError::DriftSpotBalanceError)?,
+                get_perp_base_asset_amount(&drift_subaccount, &*self.drift_perp_market.load()?),
+            )
+        };
+        let base_token_decimals = self.drift_base_spot_market.load()?.decimals;
+        let hedge_base_token_amount = match self.vault.hedge_mode {
+            VaultHedgeMode::SpotBorrow => drift_borrow_amount,
+            VaultHedgeMode::PerpShort => get_base_token_amount_from_perp(
+                drift_perp_base_asset_amount.min(0).unsigned_abs(),
+                base_token_decimals,
+            )
+            .ok_or(SurfError::BaseTokenOverflow)?,
+        };
+        let excess_hedge_base_amount = self
+            .vault
+            .get_excess_hedge_base_amount(hedge_base_token_amount, whirlpool_base_token_amount)?;
+        if excess_hedge_base_amount == 0 {
+            return Ok(());
+        }
+
+        let (repay_base_amount, perp_close_base_asset_amount) = match self.vault.hedge_mode {
+            VaultHedgeMode::SpotBorrow => {
+                let repay_base_amount =
+                    excess_hedge_base_amount.min(self.vault.idle_base_token_amount);
+                if repay_base_amount > 0 {
+                    drift_cpi::deposit(
+                        self.get_drift_deposit_context(signer_seeds),
+                        self.vault.drift_base_spot_market_index,
+                        repay_base_amount,
+                        true,
+                    )?;
+                    self.vault
+                        .update_idle_token_amounts(-(repay_base_amount as i128), 0)?;
+                }
+                (repay_base_amount, 0)
+            }
+            VaultHedgeMode::PerpShort => {
+                let perp_close_base_asset_amount = get_perp_order_base_asset_amount(
+                    excess_hedge_base_amount,
+                    base_token_decimals,
+                    self.drift_perp_market.load()?.amm.order_step_size,
+                )
+                .ok_or(SurfError::BaseTokenOverflow)?
+                .min(drift_perp_base_asset_amount.min(0).unsigned_abs());
+                if perp_close_base_asset_amount > 0 {
+                    drift_cpi::place_and_take_perp_order(
+                        self.get_drift_place_and_take_context(signer_seeds),
+                        get_perp_market_order_params(
+                            PositionDirection::Long,
+                            perp_close_base_asset_amount,
+                            self.vault.drift_perp_market_index,
+                            true,
+                            base_token_oracle_price,
+                            Vault::MAX_SWAP_SLIPPAGE_BPS,
+                        )?,
+                        None,
+                    )?;
+                    drift_cpi::settle_pnl(
+                        self.get_drift_settle_pnl_context(signer_seeds),
+                        self.vault.drift_perp_market_index,
+                    )?;
+                }
+                (0, perp_close_base_asset_amount)
+            }
+        };
+
+        emit!(VaultHedgeReduced {
+            vault: self.vault.key(),
+            hedge_base_token_amount,
+            target_hedge_base_token_amount: hedge_base_token_amount - excess_hedge_base_amount,
+            drift_deposit_base_token_amount: repay_base_amount,
+            drift_perp_close_base_asset_amount: perp_close_base_asset_amount,
+        });
+        Ok(())
+    }
+
+    pub fn update_vault_status(&mut self) -> Result<()> {
+        let drift_subaccount = self.drift_subaccount.load()?;
+        if self.vault.update_status(&drift_subaccount) {
+            emit!(VaultRecoveryStarted {
+                vault: self.vault.key(),
+                drift_next_liquidation_id: drift_subaccount.next_liquidation_id,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get_base_token_oracle_price(&self) -> Result<i64> {
+        let drift_base_spot_market = self.drift_base_spot_market.load()?;
+        get_oracle_price(
+            &self.drift_base_token_oracle.to_account_info(),
+            &drift_base_spot_market,
+            &self.drift_state.oracle_guard_rails,
+            Clock::get()?.slot,
+        )
+    }
+
+    // Swap can not pay more than oracle value of base tokens with max swap slippage
+    pub fn get_max_swap_quote_amount(
+        &self,
+        base_token_amount: u64,
+        base_token_oracle_price: i64,
+    ) -> Result<u64> {
+        let base_token_value = get_quote_value(
+            base_token_amount as i128,
+            base_token_oracle_price,
+            self.drift_base_spot_market.load()?.decimals,
+        )?;
+        mul_div(
+            base_token_value.unsigned_abs(),
+            (BPS_DENOMINATOR + Vault::MAX_SWAP_SLIPPAGE_BPS) as u128,
+            BPS_DENOMINATOR as u128,
+            true,
+        )
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(SurfError::QuoteTokenOverflow.into())
+    }
+
+    // Whirlpool price has to be close to oracle price, so decreased liquidity returns at least
+    // its token amounts at the edges of the allowed deviation band
+    pub fn get_decrease_liquidity_min_amounts(
+        &self,
+        liquidity: u128,
+        base_token_oracle_price: i64,
+    ) -> Result<(u64, u64)> {
+        let base_token_decimals = self.drift_base_spot_market.load()?.decimals;
+        let quote_token_decimals = self.drift_quote_spot_market.load()?.decimals;
+        let oracle_sqrt_price = get_oracle_sqrt_price(
+            base_token_oracle_price,
+            base_token_decimals,
+            quote_token_decimals,
+        )?;
+        Vault::validate_whirlpool_sqrt_price(self.whirlpool.sqrt_price, oracle_sqrt_price)?;
+
+        let get_band_sqrt_price = |deviation_factor: u64, round_up: bool| -> Result<u128> {
+            let band_price = mul_div(
+                base_token_oracle_price as u128,
+                deviation_factor as u128,
+                BPS_DENOMINATOR as u128,
+                round_up,
+            )
+            .and_then(|price| i64::try_from(price).ok())
+            .ok_or(SurfError::MathOverflow)?;
+            get_oracle_sqrt_price(band_price, base_token_decimals, quote_token_decimals)
+        };
+        let lower_band_sqrt_price = get_band_sqrt_price(
+            BPS_DENOMINATOR - Vault::MAX_WHIRLPOOL_PRICE_DEVIATION_BPS,
+            false,
+        )?;
+        let upper_band_sqrt_price = get_band_sqrt_price(
+            BPS_DENOMINATOR + Vault::MAX_WHIRLPOOL_PRICE_DEVIATION_BPS,
+            true,
+        )?;
+
+        // Base token amount falls and quote token amount rises with price
+        let lower_sqrt_price = sqrt_price_from_tick_index(self.whirlpool_position.tick_lower_index);
+        let upper_sqrt_price = sqrt_price_from_tick_index(self.whirlpool_position.tick_upper_index);
+        let (min_base_token_amount, _) = get_token_amounts_from_liquidity(
+            liquidity,
+            upper_band_sqrt_price,
+            lower_sqrt_price,
+            upper_sqrt_price,
+            false,
+        )?;
+        let (_, min_quote_token_amount) = get_token_amounts_from_liquidity(
+            liquidity,
+            lower_band_sqrt_price,
+            lower_sqrt_price,
+            upper_sqrt_price,
+            false,
+        )?;
+        Ok((min_base_token_amount, min_quote_token_amount))
+    }
+
+    pub fn get_health_ratio(&self, base_token_oracle_price: i64) -> Result<u64> {
+        get_health_ratio(
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
+    pub fn get_margin(&self, base_token_oracle_price: i64) -> Result<(u128, u128)> {
+        get_margin(
+            &*self.drift_subaccount.load()?,
+            &*self.drift_base_spot_market.load()?,
+            &*self.drift_quote_spot_market.load()?,
+            &*self.drift_perp_market.load()?,
+            base_token_oracle_price,
+        )
+    }
+
+    pub fn get_whirlpool_decrease_liquidity_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, DecreaseLiquidity<'info>> {
+        let decrease_liq_accounts = DecreaseLiquidity {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.vault.to_account_info(),
+            position: self.whirlpool_position.to_account_info(),
+            position_token_account: self.whirlpool_position_token_account.to_account_info(),
+            token_owner_account_a: self.vault_base_token_account.to_account_info(),
+            token_owner_account_b: self.vault_quote_token_account.to_account_info(),
+            token_vault_a: self.whirlpool_base_token_vault.to_account_info(),
+            token_vault_b: self.whirlpool_quote_token_vault.to_account_info(),
+            tick_array_lower: self.whirlpool_position_tick_array_lower.to_account_info(),
+            tick_array_upper: self.whirlpool_position_tick_array_upper.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext::new(
+            self.whirlpool_program.to_account_info(),
+            decrease_liq_accounts,
+        )
+    }
+
+    pub fn get_swap_context(&self) -> CpiContext<'_, '_, '_, 'info, Swap<'info>> {
+        let swap_accounts = Swap {
+            token_authority: self.vault.to_account_info(),
+            whirlpool: self.whirlpool.to_account_info(),
+            token_owner_account_a: self.vault_base_token_account.to_account_info(),
+            token_owner_account_b: self.vault_quote_token_account.to_account_info(),
+            token_vault_a: self.whirlpool_base_token_vault.to_account_info(),
+            token_vault_b: self.whirlpool_quote_token_vault.to_account_info(),
+            tick_array0: self.swap_tick_array_0.to_account_info(),
+            tick_array1: self.swap_tick_array_1.to_account_info(),
+            tick_array2: self.swap_tick_array_2.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            oracle: self.swap_oracle.to_account_info(),
+        };
+        CpiContext::new(self.whirlpool_program.to_account_info(), swap_accounts)
+    }
+
+    // Drift expects oracles first, then spot markets and perp markets
+    pub fn get_drift_remaining_accounts(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.drift_base_token_oracle.to_account_info(),
+            self.drift_quote_spot_market.to_account_info(),
+            self.drift_base_spot_market.to_account_info(),
+            self.drift_perp_market.to_account_info(),
+        ]
+    }
+
+    pub fn get_drift_deposit_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftDeposit<'info>> {
+        let deposit_accounts = DriftDeposit {
+            state: self.drift_state.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_base_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_base_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: deposit_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_collateral_deposit_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftDeposit<'info>> {
+        let deposit_accounts = DriftDeposit {
+            state: self.drift_state.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_quote_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: deposit_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_place_and_take_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, PlaceAndTake<'info>> {
+        let place_and_take_accounts = PlaceAndTake {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: place_and_take_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_settle_pnl_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, SettlePNL<'info>> {
+        let settle_pnl_accounts = SettlePNL {
+            state: self.drift_state.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: settle_pnl_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+
+    pub fn get_drift_withdraw_context<'a>(
+        &'a self,
+        signer_seeds: &'a [&[&[u8]]],
+    ) -> CpiContext<'_, '_, '_, 'info, DriftWithdraw<'info>> {
+        let withdraw_accounts = DriftWithdraw {
+            state: self.drift_state.to_account_info(),
+            drift_signer: self.drift_signer.to_account_info(),
+            user_stats: self.drift_stats.to_account_info(),
+            user: self.drift_subaccount.to_account_info(),
+            authority: self.vault.to_account_info(),
+            spot_market_vault: self.drift_quote_spot_market_vault.to_account_info(),
+            user_token_account: self.vault_quote_token_account.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        CpiContext {
+            program: self.drift_program.to_account_info(),
+            accounts: withdraw_accounts,
+            remaining_accounts: self.get_drift_remaining_accounts(),
+            signer_seeds,
+        }
+    }
+}
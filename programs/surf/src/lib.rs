@@ -3,11 +3,13 @@ use anchor_lang::prelude::*;
 declare_id!("4wVrbfSHxmhevzPzNfdpmVkJ2jqNRy6RYt4TxcHsnfSo");
 
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
 
 use instructions::*;
+use state::{VaultAccessMode, VaultHedgeMode};
 
 #[program]
 pub mod surf {
@@ -35,7 +37,229 @@ pub mod surf {
         open_whirlpool_position::handler(ctx, position_bump, tick_lower_index, tick_upper_index)
     }
 
-    pub fn deposit(ctx: Context<Deposit>, input_quote_amount: u64) -> Result<()> {
-        deposit::handler(ctx, input_quote_amount)
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        input_quote_amount: u64,
+        min_shares_out: u64,
+    ) -> Result<()> {
+        deposit::handler(ctx, input_quote_amount, min_shares_out)
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        shares: u64,
+        min_base_out: u64,
+        min_quote_out: u64,
+        close_user_position: bool,
+    ) -> Result<()> {
+        withdraw::handler(
+            ctx,
+            shares,
+            min_base_out,
+            min_quote_out,
+            close_user_position,
+        )
+    }
+
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, shares: u64) -> Result<()> {
+        request_withdraw::handler(ctx, shares)
+    }
+
+    pub fn claim_withdraw(
+        ctx: Context<ClaimWithdraw>,
+        min_base_out: u64,
+        min_quote_out: u64,
+    ) -> Result<()> {
+        claim_withdraw::handler(ctx, min_base_out, min_quote_out)
+    }
+
+    pub fn sync_vault_idle_token_amounts(ctx: Context<SyncVaultIdleTokenAmounts>) -> Result<()> {
+        sync_vault_idle_token_amounts::handler(ctx)
+    }
+
+    pub fn transfer_user_position(ctx: Context<TransferUserPosition>) -> Result<()> {
+        transfer_user_position::handler(ctx)
+    }
+
+    pub fn split_user_position(ctx: Context<SplitUserPosition>, shares: u64) -> Result<()> {
+        split_user_position::handler(ctx, shares)
+    }
+
+    pub fn merge_user_positions(ctx: Context<MergeUserPositions>) -> Result<()> {
+        merge_user_positions::handler(ctx)
+    }
+
+    pub fn close_user_position(ctx: Context<CloseUserPosition>) -> Result<()> {
+        close_user_position::handler(ctx)
+    }
+
+    pub fn unwind_vault(ctx: Context<UnwindVault>) -> Result<()> {
+        unwind_vault::handler(ctx)
+    }
+
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        close_vault::handler(ctx)
+    }
+
+    pub fn harvest_whirlpool_fees(ctx: Context<HarvestWhirlpoolFees>) -> Result<()> {
+        harvest_whirlpool_fees::handler(ctx)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        collect_protocol_fees::handler(ctx)
+    }
+
+    pub fn set_vault_fees(
+        ctx: Context<SetVaultFees>,
+        performance_fee_bps: u16,
+        management_fee_bps: u16,
+        entry_fee_bps: u16,
+        exit_fee_bps: u16,
+    ) -> Result<()> {
+        set_vault_fees::handler(
+            ctx,
+            performance_fee_bps,
+            management_fee_bps,
+            entry_fee_bps,
+            exit_fee_bps,
+        )
+    }
+
+    pub fn set_protocol_treasury(
+        ctx: Context<SetProtocolTreasury>,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        set_protocol_treasury::handler(ctx, treasury)
+    }
+
+    pub fn set_protocol_drift_keeper(
+        ctx: Context<SetProtocolDriftKeeper>,
+        drift_keeper: Pubkey,
+    ) -> Result<()> {
+        set_protocol_drift_keeper::handler(ctx, drift_keeper)
+    }
+
+    pub fn set_vault_deposit_caps(
+        ctx: Context<SetVaultDepositCaps>,
+        max_total_deposit: u64,
+        max_deposit_per_user: u64,
+    ) -> Result<()> {
+        set_vault_deposit_caps::handler(ctx, max_total_deposit, max_deposit_per_user)
+    }
+
+    pub fn set_vault_access_mode(
+        ctx: Context<SetVaultAccessMode>,
+        access_mode: VaultAccessMode,
+    ) -> Result<()> {
+        set_vault_access_mode::handler(ctx, access_mode)
+    }
+
+    pub fn add_depositor_allowlist_entry(
+        ctx: Context<AddDepositorAllowlistEntry>,
+        depositor: Pubkey,
+    ) -> Result<()> {
+        add_depositor_allowlist_entry::handler(ctx, depositor)
+    }
+
+    pub fn remove_depositor_allowlist_entry(
+        ctx: Context<RemoveDepositorAllowlistEntry>,
+    ) -> Result<()> {
+        remove_depositor_allowlist_entry::handler(ctx)
+    }
+
+    pub fn set_vault_withdraw_cooldown(
+        ctx: Context<SetVaultWithdrawCooldown>,
+        withdraw_cooldown_seconds: u32,
+    ) -> Result<()> {
+        set_vault_withdraw_cooldown::handler(ctx, withdraw_cooldown_seconds)
+    }
+
+    pub fn set_vault_min_health_ratio(
+        ctx: Context<SetVaultMinHealthRatio>,
+        min_health_ratio: u64,
+    ) -> Result<()> {
+        set_vault_min_health_ratio::handler(ctx, min_health_ratio)
+    }
+
+    pub fn set_vault_deleverage_config(
+        ctx: Context<SetVaultDeleverageConfig>,
+        deleverage_health_ratio: u64,
+        deleverage_target_health_ratio: u64,
+        deleverage_bounty_quote_amount: u64,
+    ) -> Result<()> {
+        set_vault_deleverage_config::handler(
+            ctx,
+            deleverage_health_ratio,
+            deleverage_target_health_ratio,
+            deleverage_bounty_quote_amount,
+        )
+    }
+
+    pub fn emergency_deleverage(ctx: Context<EmergencyDeleverage>) -> Result<()> {
+        emergency_deleverage::handler(ctx)
+    }
+
+    pub fn update_vault_status(ctx: Context<UpdateVaultStatus>) -> Result<()> {
+        update_vault_status::handler(ctx)
+    }
+
+    pub fn resolve_vault_recovery(ctx: Context<ResolveVaultRecovery>) -> Result<()> {
+        resolve_vault_recovery::handler(ctx)
+    }
+
+    pub fn set_vault_target_collateral_ratio(
+        ctx: Context<SetVaultTargetCollateralRatio>,
+        target_collateral_ratio: u64,
+    ) -> Result<()> {
+        set_vault_target_collateral_ratio::handler(ctx, target_collateral_ratio)
+    }
+
+    pub fn rebalance_drift_collateral(ctx: Context<RebalanceDriftCollateral>) -> Result<()> {
+        rebalance_drift_collateral::handler(ctx)
+    }
+
+    pub fn set_vault_hedge_ratio(
+        ctx: Context<SetVaultHedgeRatio>,
+        hedge_ratio_bps: u16,
+    ) -> Result<()> {
+        set_vault_hedge_ratio::handler(ctx, hedge_ratio_bps)
+    }
+
+    pub fn set_vault_hedge_mode(
+        ctx: Context<SetVaultHedgeMode>,
+        hedge_mode: VaultHedgeMode,
+    ) -> Result<()> {
+        set_vault_hedge_mode::handler(ctx, hedge_mode)
+    }
+
+    pub fn set_vault_hedge_mode_switch(
+        ctx: Context<SetVaultHedgeModeSwitch>,
+        hedge_mode_switch_enabled: bool,
+        hedge_mode_switch_threshold_rate: u32,
+    ) -> Result<()> {
+        set_vault_hedge_mode_switch::handler(
+            ctx,
+            hedge_mode_switch_enabled,
+            hedge_mode_switch_threshold_rate,
+        )
+    }
+
+    pub fn switch_vault_hedge_mode(ctx: Context<SwitchVaultHedgeMode>) -> Result<()> {
+        switch_vault_hedge_mode::handler(ctx)
+    }
+
+    pub fn set_vault_borrow_limits(
+        ctx: Context<SetVaultBorrowLimits>,
+        max_borrow_rate: u32,
+        max_utilization: u32,
+    ) -> Result<()> {
+        set_vault_borrow_limits::handler(ctx, max_borrow_rate, max_utilization)
+    }
+
+    pub fn set_vault_drift_delegate(
+        ctx: Context<SetVaultDriftDelegate>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        set_vault_drift_delegate::handler(ctx, delegate)
     }
 }
@@ -5,9 +5,19 @@ use anchor_lang::prelude::{Pubkey, *};
 pub struct AdminConfig {
     pub admin_key: Pubkey, // 32
     pub bump: u8,          // 1
+    // Owner of token accounts receiving protocol fees
+    pub treasury: Pubkey, // 32
+    // Only key allowed to be set as drift delegate of vault subaccounts
+    pub drift_keeper: Pubkey, // 32
 }
 
 impl AdminConfig {
-    pub const LEN: usize = 8 + 33;
+    pub const LEN: usize = 8 + 97;
     pub const NAMESPACE: &'static [u8; 12] = b"admin_config";
+
+    // Protocol wide fee maximums in basis points
+    pub const MAX_PERFORMANCE_FEE_BPS: u16 = 3_000;
+    pub const MAX_MANAGEMENT_FEE_BPS: u16 = 500;
+    pub const MAX_ENTRY_FEE_BPS: u16 = 100;
+    pub const MAX_EXIT_FEE_BPS: u16 = 100;
 }
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+// Existence of entry allows depositor to deposit into allowlist vault
+#[account]
+#[derive(Default)]
+pub struct DepositorAllowlistEntry {
+    pub bump: u8, // 1
+
+    pub vault: Pubkey,     // 32
+    pub depositor: Pubkey, // 32
+}
+
+impl DepositorAllowlistEntry {
+    pub const LEN: usize = 8 + 65;
+    pub const NAMESPACE: &'static [u8; 25] = b"depositor_allowlist_entry";
+}
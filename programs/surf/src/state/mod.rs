@@ -1,7 +1,11 @@
 pub mod admin_config;
+pub mod depositor_allowlist_entry;
 pub mod user_position;
 pub mod vault;
+pub mod withdraw_request;
 
 pub use admin_config::*;
+pub use depositor_allowlist_entry::*;
 pub use user_position::*;
 pub use vault::*;
+pub use withdraw_request::*;
@@ -1,39 +1,122 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::SurfError;
+
 #[account]
 #[derive(Default)]
 pub struct UserPosition {
     pub bump: u8, // 1
 
     pub vault: Pubkey, // 32
+    pub owner: Pubkey, // 32
 
-    pub liquidity: u128, // 16
-
-    pub fee_growth_checkpoint_base_token: u128,  // 16
-    pub fee_growth_checkpoint_quote_token: u128, // 16
-    pub fee_unclaimed_base_token: u64,           // 8
-    pub fee_unclaimed_quote_token: u64,          // 8
+    // Share token balance of owner at last sync
+    pub shares: u64, // 8
 }
 
 impl UserPosition {
-    pub const LEN: usize = 8 + 104;
+    pub const LEN: usize = 8 + 73;
     pub const NAMESPACE: &'static [u8; 13] = b"user_position";
 
-    pub fn initialize(
-        &mut self,
-        bump: u8,
-        vault_key: Pubkey,
-        liquidity: u128,
-        current_fee_growth_base_token: u128,
-        current_fee_growth_quote_token: u128,
-    ) -> () {
+    pub fn initialize(&mut self, bump: u8, vault_key: Pubkey, owner: Pubkey) -> () {
         self.bump = bump;
         self.vault = vault_key;
-        self.liquidity = liquidity;
+        self.owner = owner;
+        self.shares = 0;
+    }
+
+    // Shares can be transferred outside of surf, share token balance is the source of truth.
+    // Whirlpool fees are compounded into vault assets, every share earns them through net asset value.
+    pub fn sync_shares(&mut self, share_token_amount: u64) -> () {
+        self.shares = share_token_amount;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shares == 0
+    }
+
+    pub fn deposit(&mut self, shares: u64) -> Result<()> {
+        self.shares = self
+            .shares
+            .checked_add(shares)
+            .ok_or(SurfError::SharesOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, shares: u64) -> Result<()> {
+        self.shares = self
+            .shares
+            .checked_sub(shares)
+            .ok_or(SurfError::InsufficientShares)?;
+        Ok(())
+    }
+
+    // Both positions have to be synced before moving shares
+    pub fn transfer_shares(&mut self, destination: &mut UserPosition, shares: u64) -> Result<()> {
+        if self.vault.ne(&destination.vault) {
+            return Err(SurfError::UserPositionVaultMismatch.into());
+        }
+        self.withdraw(shares)?;
+        destination.deposit(shares)
+    }
+}
+
+#[cfg(test)]
+mod test_user_position {
+    use anchor_lang::prelude::Pubkey;
+
+    use crate::{errors::SurfError, state::UserPosition};
+
+    fn user_position(vault: Pubkey, shares: u64) -> UserPosition {
+        UserPosition {
+            vault,
+            owner: Pubkey::new_unique(),
+            shares,
+            ..UserPosition::default()
+        }
+    }
+
+    mod transfer_shares {
+        use super::*;
+
+        #[test]
+        fn moves_shares_between_positions() {
+            let vault = Pubkey::new_unique();
+            let mut source = user_position(vault, 1_000);
+            let mut destination = user_position(vault, 500);
+
+            source.transfer_shares(&mut destination, 400).unwrap();
+            assert_eq!(source.shares, 600);
+            assert_eq!(destination.shares, 900);
+
+            source.transfer_shares(&mut destination, 600).unwrap();
+            assert!(source.is_empty());
+            assert_eq!(destination.shares, 1_500);
+        }
+
+        #[test]
+        fn vault_mismatch() {
+            let mut source = user_position(Pubkey::new_unique(), 1_000);
+            let mut destination = user_position(Pubkey::new_unique(), 0);
+            assert_eq!(
+                source.transfer_shares(&mut destination, 100),
+                Err(SurfError::UserPositionVaultMismatch.into())
+            );
+            assert_eq!(source.shares, 1_000);
+            assert_eq!(destination.shares, 0);
+        }
 
-        self.fee_growth_checkpoint_base_token = current_fee_growth_base_token;
-        self.fee_growth_checkpoint_quote_token = current_fee_growth_quote_token;
-        self.fee_unclaimed_base_token = 0;
-        self.fee_unclaimed_quote_token = 0;
+        #[test]
+        fn insufficient_shares() {
+            let vault = Pubkey::new_unique();
+            let mut source = user_position(vault, 1_000);
+            let mut destination = user_position(vault, 0);
+            assert_eq!(
+                source.transfer_shares(&mut destination, 1_001),
+                Err(SurfError::InsufficientShares.into())
+            );
+            assert_eq!(source.shares, 1_000);
+            assert_eq!(destination.shares, 0);
+        }
     }
 }
@@ -1,4 +1,62 @@
 use anchor_lang::prelude::*;
+use drift::state::{perp_market::PerpMarket, spot_market::SpotMarket, user::User as DriftUser};
+use whirlpools::Position as WhirlpoolPosition;
+use whirlpools_client::math::sqrt_price_from_tick_index;
+
+use crate::{
+    errors::SurfError,
+    state::AdminConfig,
+    utils::{
+        drift::{
+            oracle::{get_oracle_sqrt_price, get_quote_value},
+            perp::get_perp_position_value,
+            rates::SPOT_UTILIZATION_PRECISION,
+            spot_balance::get_spot_token_amount,
+        },
+        math::{mul_div, mul_div_u64, BPS_DENOMINATOR},
+        orca::position_math::get_token_amounts_from_liquidity,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VaultAccessMode {
+    Open,
+    // Deposits require DepositorAllowlistEntry of depositor, withdrawals stay open
+    Allowlist,
+}
+
+impl Default for VaultAccessMode {
+    fn default() -> Self {
+        VaultAccessMode::Open
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VaultStatus {
+    Normal,
+    // Drift subaccount was liquidated, deposits are blocked until admin resolves recovery
+    Recovery,
+}
+
+impl Default for VaultStatus {
+    fn default() -> Self {
+        VaultStatus::Normal
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VaultHedgeMode {
+    // Base tokens are borrowed from drift base spot market
+    SpotBorrow,
+    // Base tokens are shorted on drift perp market, quote tokens are the only collateral
+    PerpShort,
+}
+
+impl Default for VaultHedgeMode {
+    fn default() -> Self {
+        VaultHedgeMode::SpotBorrow
+    }
+}
 
 #[account]
 #[derive(Default)]
@@ -13,15 +71,22 @@ pub struct Vault {
     pub quote_token_mint: Pubkey,    // 32
     pub quote_token_account: Pubkey, // 32
 
+    pub share_mint: Pubkey, // 32
+
     pub drift_stats: Pubkey,      // 32
     pub drift_subaccount: Pubkey, // 32
 
-    pub liquidity: u128, // 16
-    // Total fee per one unit of liquidity over the lifetime of vault
-    pub base_token_total_fee_growth: u128,  // 16
-    pub quote_token_total_fee_growth: u128, // 16
+    pub drift_base_spot_market_index: u16,  // 2
+    pub drift_quote_spot_market_index: u16, // 2
+
+    pub liquidity: u128,   // 16
+    pub total_shares: u64, // 8
 
-    // Unclaimed fee per one unit of liquidity from previous position after price range adjustment
+    // Vault token account balances accounted in net asset value
+    // Tokens sent directly to vault token accounts are not included
+    pub idle_base_token_amount: u64,  // 8
+    pub idle_quote_token_amount: u64, // 8
+    // Unclaimed fee per one share from previous position after price range adjustment
     pub base_token_fee_unclaimed: u128,  // 16
     pub quote_token_fee_unclaimed: u128, // 16
 
@@ -42,11 +107,83 @@ pub struct Vault {
     pub vault_upper_tick_index: i32,           // 4
     pub vault_lower_tick_index: i32,           // 4
     pub last_hedge_adjustment_tick_index: i32, // 4
+
+    // Protocol fees in basis points, capped by AdminConfig maximums
+    pub performance_fee_bps: u16, // 2
+    // Annualized, accrued on net asset value by minting shares to treasury
+    pub management_fee_bps: u16, // 2
+    pub entry_fee_bps: u16,      // 2
+    pub exit_fee_bps: u16,       // 2
+
+    // Accrued protocol fees not yet collected to treasury
+    // Shares are included in total shares, tokens are not included in idle balances
+    pub protocol_fee_shares: u64,             // 8
+    pub protocol_fee_base_token_amount: u64,  // 8
+    pub protocol_fee_quote_token_amount: u64, // 8
+    pub last_management_fee_timestamp: i64,   // 8
+
+    // Deposit caps denominated in quote token, zero means no cap
+    pub max_total_deposit: u64,    // 8
+    pub max_deposit_per_user: u64, // 8
+
+    pub access_mode: VaultAccessMode, // 1
+
+    // Zero means instant withdraw, otherwise withdrawals go through withdraw requests
+    pub withdraw_cooldown_seconds: u32, // 4
+
+    // Minimal drift subaccount health ratio in HEALTH_RATIO_PRECISION, zero disables the check
+    pub min_health_ratio: u64, // 8
+
+    // Anyone can deleverage vault below deleverage health ratio, zero disables deleverage
+    pub deleverage_health_ratio: u64,        // 8
+    pub deleverage_target_health_ratio: u64, // 8
+    // Paid to caller from accrued protocol quote token fees
+    pub deleverage_bounty_quote_amount: u64, // 8
+
+    pub status: VaultStatus, // 1
+    // Drift increments next liquidation id of subaccount on every liquidation
+    pub drift_next_liquidation_id: u16, // 2
+
+    // Drift collateral value per hedged base token value in BPS_DENOMINATOR, zero disables rebalance
+    pub target_collateral_ratio: u64, // 8
+
+    // Share of whirlpool base tokens hedged on drift, zero skips drift entirely
+    pub hedge_ratio_bps: u16, // 2
+
+    pub hedge_mode: VaultHedgeMode,   // 1
+    pub drift_perp_market_index: u16, // 2
+
+    // Keeper can switch hedge mode when the other instrument is cheaper by threshold
+    // Threshold is an annual rate in SPOT_RATE_PRECISION
+    pub hedge_mode_switch_enabled: bool,       // 1
+    pub hedge_mode_switch_threshold_rate: u32, // 4
+
+    // Deposits are refused if borrowing the hedge would exceed these limits, zero means no limit
+    // Borrow rate is an annual rate in SPOT_RATE_PRECISION, utilization in SPOT_UTILIZATION_PRECISION
+    pub max_borrow_rate: u32, // 4
+    pub max_utilization: u32, // 4
+
+    // Keeper allowed to place and cancel orders on drift subaccount, default if not delegated
+    pub drift_delegate: Pubkey, // 32
 }
 
 impl Vault {
-    pub const LEN: usize = 8 + 368;
+    pub const LEN: usize = 8 + 550;
     pub const NAMESPACE: &'static [u8; 5] = b"vault";
+    pub const SHARE_MINT_NAMESPACE: &'static [u8; 10] = b"share_mint";
+    // Shares locked forever on first deposit, protects against share inflation
+    pub const DEAD_SHARES: u64 = 1_000;
+    pub const MIN_DEPOSIT_QUOTE_AMOUNT: u64 = 1_000_000;
+    pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+    // Drift initializes next liquidation id of new subaccount to 1
+    pub const DRIFT_INITIAL_NEXT_LIQUIDATION_ID: u16 = 1;
+    // Hedged base token amount can change at most by 1% when hedge mode is switched
+    pub const MAX_HEDGE_MODE_SWITCH_DELTA_BPS: u64 = 100;
+    // Deposit and withdraw swaps can pay at most 1% over oracle value of swapped base tokens,
+    // perp orders fill at most 1% worse than oracle price
+    pub const MAX_SWAP_SLIPPAGE_BPS: u64 = 100;
+    // Whirlpool price can differ at most by 1% from oracle price when liquidity is pulled
+    pub const MAX_WHIRLPOOL_PRICE_DEVIATION_BPS: u64 = 100;
 
     pub fn initialize(
         &mut self,
@@ -56,11 +193,15 @@ impl Vault {
         quote_token_mint: Pubkey,
         base_token_account: Pubkey,
         quote_token_account: Pubkey,
+        share_mint: Pubkey,
         drift_stats: Pubkey,
         drift_subaccount: Pubkey,
+        drift_base_spot_market_index: u16,
+        drift_quote_spot_market_index: u16,
         full_tick_range: u32,
         vault_tick_range: u32,
         hedge_tick_range: u32,
+        current_timestamp: i64,
     ) -> () {
         self.bump = bump;
         self.whirlpool = whirlpool;
@@ -69,14 +210,18 @@ impl Vault {
         self.quote_token_mint = quote_token_mint;
         self.base_token_account = base_token_account;
         self.quote_token_account = quote_token_account;
+        self.share_mint = share_mint;
 
         self.drift_stats = drift_stats;
         self.drift_subaccount = drift_subaccount;
+        self.drift_base_spot_market_index = drift_base_spot_market_index;
+        self.drift_quote_spot_market_index = drift_quote_spot_market_index;
 
         self.liquidity = 0;
+        self.total_shares = 0;
+        self.idle_base_token_amount = 0;
+        self.idle_quote_token_amount = 0;
 
-        self.base_token_total_fee_growth = 0;
-        self.quote_token_total_fee_growth = 0;
         self.base_token_fee_unclaimed = 0;
         self.quote_token_fee_unclaimed = 0;
 
@@ -90,6 +235,45 @@ impl Vault {
         self.vault_upper_tick_index = 0;
         self.vault_lower_tick_index = 0;
         self.last_hedge_adjustment_tick_index = 0;
+
+        self.performance_fee_bps = 0;
+        self.management_fee_bps = 0;
+        self.entry_fee_bps = 0;
+        self.exit_fee_bps = 0;
+
+        self.protocol_fee_shares = 0;
+        self.protocol_fee_base_token_amount = 0;
+        self.protocol_fee_quote_token_amount = 0;
+        self.last_management_fee_timestamp = current_timestamp;
+
+        self.max_total_deposit = 0;
+        self.max_deposit_per_user = 0;
+
+        self.access_mode = VaultAccessMode::Open;
+        self.withdraw_cooldown_seconds = 0;
+        self.min_health_ratio = 0;
+
+        self.deleverage_health_ratio = 0;
+        self.deleverage_target_health_ratio = 0;
+        self.deleverage_bounty_quote_amount = 0;
+
+        self.status = VaultStatus::Normal;
+        self.drift_next_liquidation_id = Self::DRIFT_INITIAL_NEXT_LIQUIDATION_ID;
+
+        self.target_collateral_ratio = 0;
+
+        self.hedge_ratio_bps = BPS_DENOMINATOR as u16;
+
+        self.hedge_mode = VaultHedgeMode::SpotBorrow;
+        self.drift_perp_market_index = 0;
+
+        self.hedge_mode_switch_enabled = false;
+        self.hedge_mode_switch_threshold_rate = 0;
+
+        self.max_borrow_rate = 0;
+        self.max_utilization = 0;
+
+        self.drift_delegate = Pubkey::default();
     }
 
     pub fn open_position(&mut self, tick_current_index: i32, whirlpool_position: Pubkey) -> () {
@@ -106,9 +290,1215 @@ impl Vault {
 
     pub fn close_position(&mut self) -> () {
         self.is_active = false;
+        self.whirlpool_position = Pubkey::default();
 
         self.vault_lower_tick_index = 0;
         self.vault_upper_tick_index = 0;
         self.last_hedge_adjustment_tick_index = 0;
     }
+
+    // Liquidity and drift balances left after every user share is burned back only dead shares.
+    // Management fee is accrued first, treasury has to collect every protocol fee before unwinding.
+    pub fn unwind(&mut self, share_supply: u64, current_timestamp: i64) -> Result<()> {
+        if !self.is_active {
+            return Err(SurfError::VaultPositionNotActive.into());
+        }
+        if share_supply != 0 {
+            return Err(SurfError::VaultSharesNotZero.into());
+        }
+        self.accrue_management_fee(current_timestamp)?;
+        if self.protocol_fee_shares != 0
+            || self.protocol_fee_base_token_amount != 0
+            || self.protocol_fee_quote_token_amount != 0
+        {
+            return Err(SurfError::ProtocolFeesNotCollected.into());
+        }
+        self.liquidity = 0;
+        self.total_shares = 0;
+        self.idle_base_token_amount = 0;
+        self.idle_quote_token_amount = 0;
+        self.close_position();
+        Ok(())
+    }
+
+    // Value of all vault assets denominated in quote token
+    // Whirlpool position + drift deposits - drift borrows + drift perp pnl + accounted idle balances
+    // Position is split into tokens at oracle price, so whirlpool price moves can not change value
+    pub fn net_asset_value(
+        &self,
+        whirlpool_position: &WhirlpoolPosition,
+        drift_subaccount: &DriftUser,
+        drift_base_spot_market: &SpotMarket,
+        drift_quote_spot_market: &SpotMarket,
+        drift_perp_market: &PerpMarket,
+        base_token_oracle_price: i64,
+    ) -> Result<u64> {
+        let oracle_sqrt_price = get_oracle_sqrt_price(
+            base_token_oracle_price,
+            drift_base_spot_market.decimals,
+            drift_quote_spot_market.decimals,
+        )?;
+        let (position_base_token_amount, position_quote_token_amount) =
+            get_token_amounts_from_liquidity(
+                whirlpool_position.liquidity,
+                oracle_sqrt_price,
+                sqrt_price_from_tick_index(whirlpool_position.tick_lower_index),
+                sqrt_price_from_tick_index(whirlpool_position.tick_upper_index),
+                false,
+            )?;
+
+        let drift_base_token_amount =
+            get_spot_token_amount(drift_subaccount, drift_base_spot_market)?;
+        let drift_quote_token_amount =
+            get_spot_token_amount(drift_subaccount, drift_quote_spot_market)?;
+
+        let base_token_amount = (position_base_token_amount as i128)
+            .checked_add(self.idle_base_token_amount as i128)
+            .and_then(|amount| amount.checked_add(drift_base_token_amount))
+            .ok_or(SurfError::NetAssetValueOverflow)?;
+        let quote_token_amount = (position_quote_token_amount as i128)
+            .checked_add(self.idle_quote_token_amount as i128)
+            .and_then(|amount| amount.checked_add(drift_quote_token_amount))
+            .ok_or(SurfError::NetAssetValueOverflow)?;
+
+        let base_token_value = get_quote_value(
+            base_token_amount,
+            base_token_oracle_price,
+            drift_base_spot_market.decimals,
+        )?;
+        let (perp_unrealized_pnl, _) =
+            get_perp_position_value(drift_subaccount, drift_perp_market, base_token_oracle_price)?;
+        let net_asset_value = quote_token_amount
+            .checked_add(base_token_value)
+            .and_then(|value| value.checked_add(perp_unrealized_pnl))
+            .ok_or(SurfError::NetAssetValueOverflow)?;
+
+        if net_asset_value < 0 {
+            return Err(SurfError::NegativeNetAssetValue.into());
+        }
+        u64::try_from(net_asset_value).map_err(|_| SurfError::NetAssetValueOverflow.into())
+    }
+
+    // Shares are minted 1:1 with deposited value for the first deposit, without dead shares
+    pub fn get_shares_from_deposit_value(
+        &self,
+        deposit_value: u64,
+        net_asset_value: u64,
+    ) -> Result<u64> {
+        if self.total_shares == 0 {
+            return deposit_value
+                .checked_sub(Self::DEAD_SHARES)
+                .filter(|shares| *shares > 0)
+                .ok_or(SurfError::DepositTooSmall.into());
+        }
+        // Shares of vault without assets can not be priced
+        if net_asset_value == 0 {
+            return Err(SurfError::ZeroNetAssetValue.into());
+        }
+        mul_div_u64(deposit_value, self.total_shares, net_asset_value, false)
+            .ok_or(SurfError::SharesOverflow.into())
+    }
+
+    pub fn deposit(&mut self, liquidity: u128, shares: u64) -> Result<()> {
+        let dead_shares = if self.total_shares == 0 {
+            Self::DEAD_SHARES
+        } else {
+            0
+        };
+        self.liquidity = self
+            .liquidity
+            .checked_add(liquidity)
+            .ok_or(SurfError::LiquidityOverflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_add(shares)
+            .and_then(|total_shares| total_shares.checked_add(dead_shares))
+            .ok_or(SurfError::SharesOverflow)?;
+        Ok(())
+    }
+
+    // Balance changes of vault token accounts made by the vault itself
+    pub fn update_idle_token_amounts(
+        &mut self,
+        base_token_amount_delta: i128,
+        quote_token_amount_delta: i128,
+    ) -> Result<()> {
+        self.idle_base_token_amount =
+            u64::try_from((self.idle_base_token_amount as i128) + base_token_amount_delta)
+                .map_err(|_| SurfError::BaseTokenOverflow)?;
+        self.idle_quote_token_amount =
+            u64::try_from((self.idle_quote_token_amount as i128) + quote_token_amount_delta)
+                .map_err(|_| SurfError::QuoteTokenOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, liquidity: u128, shares: u64) -> Result<()> {
+        self.liquidity = self
+            .liquidity
+            .checked_sub(liquidity)
+            .ok_or(SurfError::LiquidityUnderflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(SurfError::InsufficientShares)?;
+        Ok(())
+    }
+
+    pub fn set_fees(
+        &mut self,
+        performance_fee_bps: u16,
+        management_fee_bps: u16,
+        entry_fee_bps: u16,
+        exit_fee_bps: u16,
+    ) -> Result<()> {
+        if performance_fee_bps > AdminConfig::MAX_PERFORMANCE_FEE_BPS {
+            return Err(SurfError::PerformanceFeeTooHigh.into());
+        }
+        if management_fee_bps > AdminConfig::MAX_MANAGEMENT_FEE_BPS {
+            return Err(SurfError::ManagementFeeTooHigh.into());
+        }
+        if entry_fee_bps > AdminConfig::MAX_ENTRY_FEE_BPS {
+            return Err(SurfError::EntryFeeTooHigh.into());
+        }
+        if exit_fee_bps > AdminConfig::MAX_EXIT_FEE_BPS {
+            return Err(SurfError::ExitFeeTooHigh.into());
+        }
+        self.performance_fee_bps = performance_fee_bps;
+        self.management_fee_bps = management_fee_bps;
+        self.entry_fee_bps = entry_fee_bps;
+        self.exit_fee_bps = exit_fee_bps;
+        Ok(())
+    }
+
+    // Operations leaving drift subaccount below minimal health ratio are allowed only if they improve it
+    pub fn validate_health_ratio(
+        &self,
+        pre_health_ratio: u64,
+        post_health_ratio: u64,
+    ) -> Result<()> {
+        if post_health_ratio < self.min_health_ratio && post_health_ratio < pre_health_ratio {
+            return Err(SurfError::DriftHealthRatioTooLow.into());
+        }
+        Ok(())
+    }
+
+    pub fn is_drift_subaccount_liquidated(&self, drift_subaccount: &DriftUser) -> bool {
+        drift_subaccount.is_being_liquidated()
+            || drift_subaccount.is_bankrupt()
+            || drift_subaccount.next_liquidation_id != self.drift_next_liquidation_id
+    }
+
+    // Returns true if vault entered recovery
+    pub fn update_status(&mut self, drift_subaccount: &DriftUser) -> bool {
+        if self.status == VaultStatus::Recovery
+            || !self.is_drift_subaccount_liquidated(drift_subaccount)
+        {
+            return false;
+        }
+        self.status = VaultStatus::Recovery;
+        true
+    }
+
+    // Liquidation losses are already reflected in drift balances backing every share
+    pub fn resolve_recovery(&mut self, drift_subaccount: &DriftUser) -> Result<()> {
+        if self.status != VaultStatus::Recovery {
+            return Err(SurfError::VaultNotInRecovery.into());
+        }
+        if drift_subaccount.is_being_liquidated() || drift_subaccount.is_bankrupt() {
+            return Err(SurfError::DriftSubaccountBeingLiquidated.into());
+        }
+        self.status = VaultStatus::Normal;
+        self.drift_next_liquidation_id = drift_subaccount.next_liquidation_id;
+        Ok(())
+    }
+
+    // Drift subaccount can be delegated only to the keeper set through surf
+    pub fn validate_drift_delegate(&self, drift_subaccount: &DriftUser) -> Result<()> {
+        if drift_subaccount.delegate.ne(&self.drift_delegate) {
+            return Err(SurfError::DriftDelegateMismatch.into());
+        }
+        Ok(())
+    }
+
+    // Hedge instrument can be replaced only while it holds no hedge, dead shares keep vault liquidity non-zero
+    // Live hedge is moved between instruments by the permissionless hedge mode switch
+    pub fn set_hedge_mode(
+        &mut self,
+        hedge_mode: VaultHedgeMode,
+        drift_perp_market_index: u16,
+        drift_borrow_amount: u64,
+        drift_perp_base_asset_amount: i64,
+    ) -> Result<()> {
+        let is_spot_borrow_replaced = self.hedge_mode == VaultHedgeMode::SpotBorrow
+            && hedge_mode != VaultHedgeMode::SpotBorrow;
+        let is_perp_short_replaced = (self.hedge_mode == VaultHedgeMode::PerpShort
+            && hedge_mode != VaultHedgeMode::PerpShort)
+            || self.drift_perp_market_index != drift_perp_market_index;
+        if (is_spot_borrow_replaced && drift_borrow_amount != 0)
+            || (is_perp_short_replaced && drift_perp_base_asset_amount != 0)
+        {
+            return Err(SurfError::VaultHedgeNotClosed.into());
+        }
+        self.hedge_mode = hedge_mode;
+        self.drift_perp_market_index = drift_perp_market_index;
+        Ok(())
+    }
+
+    pub fn set_hedge_mode_switch(&mut self, enabled: bool, threshold_rate: u32) -> () {
+        self.hedge_mode_switch_enabled = enabled;
+        self.hedge_mode_switch_threshold_rate = threshold_rate;
+    }
+
+    // Rates are annual costs of spot borrow and perp short
+    pub fn is_hedge_mode_switch_beneficial(
+        &self,
+        spot_borrow_rate: i128,
+        perp_short_funding_rate: i128,
+    ) -> bool {
+        let (current_rate, target_rate) = match self.hedge_mode {
+            VaultHedgeMode::SpotBorrow => (spot_borrow_rate, perp_short_funding_rate),
+            VaultHedgeMode::PerpShort => (perp_short_funding_rate, spot_borrow_rate),
+        };
+        target_rate + (self.hedge_mode_switch_threshold_rate as i128) < current_rate
+    }
+
+    pub fn validate_hedge_mode_switch_delta(
+        pre_hedge_base_token_amount: u64,
+        post_hedge_base_token_amount: u64,
+    ) -> Result<()> {
+        let hedge_delta = pre_hedge_base_token_amount.abs_diff(post_hedge_base_token_amount);
+        let max_hedge_delta = mul_div_u64(
+            pre_hedge_base_token_amount,
+            Self::MAX_HEDGE_MODE_SWITCH_DELTA_BPS,
+            BPS_DENOMINATOR,
+            false,
+        )
+        .ok_or(SurfError::BaseTokenOverflow)?;
+        if hedge_delta > max_hedge_delta {
+            return Err(SurfError::HedgeDeltaOutOfTolerance.into());
+        }
+        Ok(())
+    }
+
+    // Prices are compared as squared sqrt prices divided by oracle sqrt price
+    pub fn validate_whirlpool_sqrt_price(
+        whirlpool_sqrt_price: u128,
+        oracle_sqrt_price: u128,
+    ) -> Result<()> {
+        let whirlpool_price = mul_div(
+            whirlpool_sqrt_price,
+            whirlpool_sqrt_price,
+            oracle_sqrt_price,
+            false,
+        );
+        let min_price = mul_div(
+            oracle_sqrt_price,
+            (BPS_DENOMINATOR - Self::MAX_WHIRLPOOL_PRICE_DEVIATION_BPS) as u128,
+            BPS_DENOMINATOR as u128,
+            false,
+        );
+        let max_price = mul_div(
+            oracle_sqrt_price,
+            (BPS_DENOMINATOR + Self::MAX_WHIRLPOOL_PRICE_DEVIATION_BPS) as u128,
+            BPS_DENOMINATOR as u128,
+            true,
+        );
+        match (whirlpool_price, min_price, max_price) {
+            (Some(price), Some(min_price), Some(max_price))
+                if price >= min_price && price <= max_price =>
+            {
+                Ok(())
+            }
+            _ => Err(SurfError::WhirlpoolPriceDeviatesFromOracle.into()),
+        }
+    }
+
+    // Hedge is moved between instruments before the flip, so vault liquidity is not restricted
+    // Target is previous hedge capped by hedge ratio of whirlpool base tokens
+    pub fn switch_hedge_mode(
+        &mut self,
+        target_hedge_base_token_amount: u64,
+        post_hedge_base_token_amount: u64,
+    ) -> Result<VaultHedgeMode> {
+        if !self.hedge_mode_switch_enabled {
+            return Err(SurfError::HedgeModeSwitchDisabled.into());
+        }
+        Self::validate_hedge_mode_switch_delta(
+            target_hedge_base_token_amount,
+            post_hedge_base_token_amount,
+        )?;
+        self.hedge_mode = match self.hedge_mode {
+            VaultHedgeMode::SpotBorrow => VaultHedgeMode::PerpShort,
+            VaultHedgeMode::PerpShort => VaultHedgeMode::SpotBorrow,
+        };
+        Ok(self.hedge_mode)
+    }
+
+    pub fn set_borrow_limits(&mut self, max_borrow_rate: u32, max_utilization: u32) -> Result<()> {
+        if max_utilization as u128 > SPOT_UTILIZATION_PRECISION {
+            return Err(SurfError::InvalidMaxUtilization.into());
+        }
+        self.max_borrow_rate = max_borrow_rate;
+        self.max_utilization = max_utilization;
+        Ok(())
+    }
+
+    pub fn validate_borrow_conditions(&self, utilization: u128, borrow_rate: u128) -> Result<()> {
+        if self.max_utilization != 0 && utilization > self.max_utilization as u128 {
+            return Err(SurfError::DriftUtilizationTooHigh.into());
+        }
+        if self.max_borrow_rate != 0 && borrow_rate > self.max_borrow_rate as u128 {
+            return Err(SurfError::DriftBorrowRateTooHigh.into());
+        }
+        Ok(())
+    }
+
+    pub fn set_hedge_ratio(&mut self, hedge_ratio_bps: u16) -> Result<()> {
+        if hedge_ratio_bps as u64 > BPS_DENOMINATOR {
+            return Err(SurfError::InvalidHedgeRatio.into());
+        }
+        self.hedge_ratio_bps = hedge_ratio_bps;
+        Ok(())
+    }
+
+    // Amount of base tokens borrowed on drift against base tokens deposited to whirlpool
+    pub fn get_hedge_base_amount(&self, whirlpool_base_token_amount: u64) -> Result<u64> {
+        mul_div_u64(
+            whirlpool_base_token_amount,
+            self.hedge_ratio_bps as u64,
+            BPS_DENOMINATOR,
+            false,
+        )
+        .ok_or(SurfError::BaseTokenOverflow.into())
+    }
+
+    // Hedge above hedge ratio of whirlpool base tokens, hedge is only reduced towards the ratio
+    pub fn get_excess_hedge_base_amount(
+        &self,
+        hedge_base_token_amount: u64,
+        whirlpool_base_token_amount: u64,
+    ) -> Result<u64> {
+        Ok(hedge_base_token_amount
+            .saturating_sub(self.get_hedge_base_amount(whirlpool_base_token_amount)?))
+    }
+
+    pub fn set_target_collateral_ratio(&mut self, target_collateral_ratio: u64) -> Result<()> {
+        if target_collateral_ratio != 0 && target_collateral_ratio <= BPS_DENOMINATOR {
+            return Err(SurfError::InvalidTargetCollateralRatio.into());
+        }
+        self.target_collateral_ratio = target_collateral_ratio;
+        Ok(())
+    }
+
+    // Positive delta has to be deposited to drift, negative withdrawn from drift
+    // Deposits to drift are limited by idle quote tokens
+    pub fn get_drift_collateral_delta(
+        &self,
+        hedge_value: u64,
+        drift_collateral_amount: u64,
+    ) -> Result<i128> {
+        let target_collateral_amount = mul_div_u64(
+            hedge_value,
+            self.target_collateral_ratio,
+            BPS_DENOMINATOR,
+            true,
+        )
+        .ok_or(SurfError::QuoteTokenOverflow)?;
+        let collateral_delta =
+            (target_collateral_amount as i128) - (drift_collateral_amount as i128);
+        if collateral_delta > 0 && self.idle_quote_token_amount == 0 {
+            return Err(SurfError::InsufficientIdleQuoteTokens.into());
+        }
+        Ok(collateral_delta.min(self.idle_quote_token_amount as i128))
+    }
+
+    pub fn set_deleverage_config(
+        &mut self,
+        deleverage_health_ratio: u64,
+        deleverage_target_health_ratio: u64,
+        deleverage_bounty_quote_amount: u64,
+    ) -> Result<()> {
+        if deleverage_health_ratio != 0 && deleverage_target_health_ratio <= deleverage_health_ratio
+        {
+            return Err(SurfError::InvalidDeleverageHealthRatios.into());
+        }
+        self.deleverage_health_ratio = deleverage_health_ratio;
+        self.deleverage_target_health_ratio = deleverage_target_health_ratio;
+        self.deleverage_bounty_quote_amount = deleverage_bounty_quote_amount;
+        Ok(())
+    }
+
+    // Bounty is limited by accrued protocol quote token fees
+    pub fn pay_deleverage_bounty(&mut self) -> u64 {
+        let bounty = self
+            .deleverage_bounty_quote_amount
+            .min(self.protocol_fee_quote_token_amount);
+        self.protocol_fee_quote_token_amount -= bounty;
+        bounty
+    }
+
+    pub fn set_deposit_caps(&mut self, max_total_deposit: u64, max_deposit_per_user: u64) -> () {
+        self.max_total_deposit = max_total_deposit;
+        self.max_deposit_per_user = max_deposit_per_user;
+    }
+
+    // Values are net asset value of vault and value of user shares after deposit
+    pub fn check_deposit_caps(&self, net_asset_value: u64, user_shares: u64) -> Result<()> {
+        if self.max_total_deposit != 0 && net_asset_value > self.max_total_deposit {
+            return Err(SurfError::VaultDepositCapExceeded.into());
+        }
+        if self.max_deposit_per_user != 0 {
+            let user_value = mul_div_u64(user_shares, net_asset_value, self.total_shares, true)
+                .ok_or(SurfError::NetAssetValueOverflow)?;
+            if user_value > self.max_deposit_per_user {
+                return Err(SurfError::UserDepositCapExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    // Mints shares worth management fee share of net asset value since last accrual
+    // fee_shares / (total_shares + fee_shares) = fee_bps * elapsed / (BPS_DENOMINATOR * SECONDS_PER_YEAR)
+    pub fn accrue_management_fee(&mut self, current_timestamp: i64) -> Result<u64> {
+        let elapsed = current_timestamp
+            .checked_sub(self.last_management_fee_timestamp)
+            .filter(|elapsed| *elapsed > 0)
+            .unwrap_or(0) as u128;
+        self.last_management_fee_timestamp =
+            current_timestamp.max(self.last_management_fee_timestamp);
+
+        if self.total_shares == 0 || self.management_fee_bps == 0 || elapsed == 0 {
+            return Ok(0);
+        }
+        let fee_numerator = (self.management_fee_bps as u128) * elapsed;
+        let fee_denominator = ((BPS_DENOMINATOR * Self::SECONDS_PER_YEAR) as u128)
+            .checked_sub(fee_numerator)
+            .filter(|denominator| *denominator > 0)
+            .ok_or(SurfError::FeeOverflow)?;
+        let fee_shares = mul_div(
+            self.total_shares as u128,
+            fee_numerator,
+            fee_denominator,
+            false,
+        )
+        .and_then(|fee_shares| u64::try_from(fee_shares).ok())
+        .ok_or(SurfError::FeeOverflow)?;
+
+        self.accrue_protocol_fee_shares(fee_shares)?;
+        Ok(fee_shares)
+    }
+
+    // Part of shares taken by entry or exit fee
+    pub fn get_fee_shares(shares: u64, fee_bps: u16) -> Result<u64> {
+        mul_div_u64(shares, fee_bps as u64, BPS_DENOMINATOR, false)
+            .ok_or(SurfError::FeeOverflow.into())
+    }
+
+    // Fee shares are owned by treasury until collected
+    pub fn accrue_protocol_fee_shares(&mut self, fee_shares: u64) -> Result<()> {
+        self.protocol_fee_shares = self
+            .protocol_fee_shares
+            .checked_add(fee_shares)
+            .ok_or(SurfError::FeeOverflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_add(fee_shares)
+            .ok_or(SurfError::SharesOverflow)?;
+        Ok(())
+    }
+
+    // Splits harvested whirlpool fees into protocol performance fee and fees compounded into vault
+    pub fn harvest_fees(
+        &mut self,
+        base_token_fee: u64,
+        quote_token_fee: u64,
+    ) -> Result<(u64, u64)> {
+        let performance_fee_bps = self.performance_fee_bps as u64;
+        let base_token_performance_fee =
+            mul_div_u64(base_token_fee, performance_fee_bps, BPS_DENOMINATOR, false)
+                .ok_or(SurfError::FeeOverflow)?;
+        let quote_token_performance_fee =
+            mul_div_u64(quote_token_fee, performance_fee_bps, BPS_DENOMINATOR, false)
+                .ok_or(SurfError::FeeOverflow)?;
+
+        self.protocol_fee_base_token_amount = self
+            .protocol_fee_base_token_amount
+            .checked_add(base_token_performance_fee)
+            .ok_or(SurfError::FeeOverflow)?;
+        self.protocol_fee_quote_token_amount = self
+            .protocol_fee_quote_token_amount
+            .checked_add(quote_token_performance_fee)
+            .ok_or(SurfError::FeeOverflow)?;
+        self.update_idle_token_amounts(
+            (base_token_fee - base_token_performance_fee) as i128,
+            (quote_token_fee - quote_token_performance_fee) as i128,
+        )?;
+        Ok((base_token_performance_fee, quote_token_performance_fee))
+    }
+
+    // Protocol fees were sent to treasury, fee shares were minted to treasury
+    pub fn collect_protocol_fees(&mut self) -> () {
+        self.protocol_fee_shares = 0;
+        self.protocol_fee_base_token_amount = 0;
+        self.protocol_fee_quote_token_amount = 0;
+    }
+}
+
+#[cfg(test)]
+mod test_vault {
+    use crate::{
+        errors::SurfError,
+        state::{Vault, VaultHedgeMode},
+    };
+
+    mod get_shares_from_deposit_value {
+        use super::*;
+
+        #[test]
+        fn first_deposit_locks_dead_shares() {
+            let vault = Vault::default();
+            assert_eq!(
+                vault.get_shares_from_deposit_value(1_000_000, 0).unwrap(),
+                1_000_000 - Vault::DEAD_SHARES
+            );
+            assert_eq!(
+                vault.get_shares_from_deposit_value(Vault::DEAD_SHARES, 0),
+                Err(SurfError::DepositTooSmall.into())
+            );
+        }
+
+        #[test]
+        fn shares_are_proportional_to_net_asset_value() {
+            let vault = Vault {
+                total_shares: 1_000_000,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault
+                    .get_shares_from_deposit_value(500_000, 2_000_000)
+                    .unwrap(),
+                250_000
+            );
+            // Rounds down in favour of vault
+            assert_eq!(
+                vault.get_shares_from_deposit_value(1, 3_000_000).unwrap(),
+                0
+            );
+        }
+
+        #[test]
+        fn zero_net_asset_value_with_shares() {
+            let vault = Vault {
+                total_shares: 1_000_000,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.get_shares_from_deposit_value(1_000_000, 0),
+                Err(SurfError::ZeroNetAssetValue.into())
+            );
+        }
+    }
+
+    mod unwind {
+        use anchor_lang::prelude::Pubkey;
+
+        use super::*;
+
+        #[test]
+        fn open_deposit_withdraw_all_and_unwind() {
+            let mut vault = Vault {
+                vault_tick_range: 400,
+                ..Vault::default()
+            };
+            let whirlpool_position = Pubkey::new_unique();
+            vault.open_position(100, whirlpool_position);
+            assert!(vault.is_active);
+            assert_eq!(vault.whirlpool_position, whirlpool_position);
+
+            let shares = vault.get_shares_from_deposit_value(1_000_000, 0).unwrap();
+            vault.deposit(1_000_000, shares).unwrap();
+            assert_eq!(vault.total_shares, 1_000_000);
+
+            // Liquidity of dead shares stays in vault after user withdraws everything
+            vault.withdraw(999_000, shares).unwrap();
+            assert_eq!(vault.liquidity, 1_000);
+            assert_eq!(vault.total_shares, Vault::DEAD_SHARES);
+
+            vault.unwind(0, 0).unwrap();
+            assert!(!vault.is_active);
+            assert_eq!(vault.whirlpool_position, Pubkey::default());
+            assert_eq!(vault.liquidity, 0);
+            assert_eq!(vault.total_shares, 0);
+            assert_eq!(vault.vault_lower_tick_index, 0);
+            assert_eq!(vault.vault_upper_tick_index, 0);
+
+            // Unwound vault can not be unwound twice
+            assert_eq!(
+                vault.unwind(0, 0),
+                Err(SurfError::VaultPositionNotActive.into())
+            );
+        }
+
+        #[test]
+        fn unwind_requires_protocol_fees_collected() {
+            let mut vault = Vault {
+                management_fee_bps: 100,
+                ..Vault::default()
+            };
+            vault.open_position(0, Pubkey::new_unique());
+            vault.deposit(1_000, 0).unwrap();
+            assert_eq!(vault.total_shares, Vault::DEAD_SHARES);
+
+            // Year of management fee on dead shares is accrued before unwinding
+            assert_eq!(
+                vault.unwind(0, Vault::SECONDS_PER_YEAR as i64),
+                Err(SurfError::ProtocolFeesNotCollected.into())
+            );
+            assert_eq!(vault.protocol_fee_shares, 10);
+            vault.collect_protocol_fees();
+
+            vault.protocol_fee_quote_token_amount = 1;
+            assert_eq!(
+                vault.unwind(0, Vault::SECONDS_PER_YEAR as i64),
+                Err(SurfError::ProtocolFeesNotCollected.into())
+            );
+            vault.collect_protocol_fees();
+
+            vault.unwind(0, Vault::SECONDS_PER_YEAR as i64).unwrap();
+            assert!(!vault.is_active);
+        }
+
+        #[test]
+        fn unwind_requires_all_user_shares_burned() {
+            let mut vault = Vault::default();
+            vault.open_position(0, Pubkey::new_unique());
+            vault.deposit(1_000_000, 999_000).unwrap();
+            assert_eq!(
+                vault.unwind(999_000, 0),
+                Err(SurfError::VaultSharesNotZero.into())
+            );
+            assert!(vault.is_active);
+            assert_eq!(vault.liquidity, 1_000_000);
+        }
+    }
+
+    mod accrue_management_fee {
+        use super::*;
+
+        fn vault_with_management_fee(management_fee_bps: u16) -> Vault {
+            Vault {
+                total_shares: 1_000_000,
+                management_fee_bps,
+                last_management_fee_timestamp: 1_000,
+                ..Vault::default()
+            }
+        }
+
+        #[test]
+        fn fee_shares_own_fee_share_of_vault_after_one_year() {
+            let mut vault = vault_with_management_fee(100);
+            let current_timestamp = 1_000 + Vault::SECONDS_PER_YEAR as i64;
+
+            // 10101 / (1000000 + 10101) is just below 1%
+            assert_eq!(
+                vault.accrue_management_fee(current_timestamp).unwrap(),
+                10_101
+            );
+            assert_eq!(vault.protocol_fee_shares, 10_101);
+            assert_eq!(vault.total_shares, 1_010_101);
+            assert_eq!(vault.last_management_fee_timestamp, current_timestamp);
+        }
+
+        #[test]
+        fn no_fee_without_elapsed_time() {
+            let mut vault = vault_with_management_fee(100);
+            assert_eq!(vault.accrue_management_fee(1_000).unwrap(), 0);
+
+            // Timestamp never moves backwards
+            assert_eq!(vault.accrue_management_fee(500).unwrap(), 0);
+            assert_eq!(vault.last_management_fee_timestamp, 1_000);
+            assert_eq!(vault.total_shares, 1_000_000);
+        }
+
+        #[test]
+        fn no_fee_without_shares_or_fee_rate() {
+            let mut vault = vault_with_management_fee(0);
+            assert_eq!(vault.accrue_management_fee(2_000).unwrap(), 0);
+            assert_eq!(vault.last_management_fee_timestamp, 2_000);
+
+            let mut vault = Vault {
+                total_shares: 0,
+                ..vault_with_management_fee(100)
+            };
+            assert_eq!(vault.accrue_management_fee(2_000).unwrap(), 0);
+            assert_eq!(vault.last_management_fee_timestamp, 2_000);
+            assert_eq!(vault.protocol_fee_shares, 0);
+        }
+    }
+
+    mod switch_hedge_mode {
+        use super::*;
+
+        #[test]
+        fn live_vault_switches_both_ways() {
+            let mut vault = Vault {
+                liquidity: 1_000_000,
+                total_shares: 1_000_000,
+                hedge_mode_switch_enabled: true,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.switch_hedge_mode(500_000, 499_000).unwrap(),
+                VaultHedgeMode::PerpShort
+            );
+            assert_eq!(
+                vault.switch_hedge_mode(499_000, 499_000).unwrap(),
+                VaultHedgeMode::SpotBorrow
+            );
+            assert_eq!(vault.liquidity, 1_000_000);
+        }
+
+        #[test]
+        fn hedge_delta_out_of_tolerance() {
+            let mut vault = Vault {
+                liquidity: 1_000_000,
+                hedge_mode_switch_enabled: true,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.switch_hedge_mode(500_000, 494_999),
+                Err(SurfError::HedgeDeltaOutOfTolerance.into())
+            );
+            assert_eq!(vault.hedge_mode, VaultHedgeMode::SpotBorrow);
+        }
+
+        #[test]
+        fn switch_disabled() {
+            let mut vault = Vault {
+                liquidity: 1_000_000,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.switch_hedge_mode(500_000, 500_000),
+                Err(SurfError::HedgeModeSwitchDisabled.into())
+            );
+        }
+    }
+
+    mod get_excess_hedge_base_amount {
+        use super::*;
+
+        #[test]
+        fn hedge_above_ratio_is_excess() {
+            let vault = Vault {
+                hedge_ratio_bps: 5_000,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault
+                    .get_excess_hedge_base_amount(1_000_000, 1_000_000)
+                    .unwrap(),
+                500_000
+            );
+            // Hedge below ratio is not increased
+            assert_eq!(
+                vault
+                    .get_excess_hedge_base_amount(400_000, 1_000_000)
+                    .unwrap(),
+                0
+            );
+        }
+
+        #[test]
+        fn zero_hedge_ratio_unwinds_whole_hedge() {
+            let vault = Vault {
+                hedge_ratio_bps: 0,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault
+                    .get_excess_hedge_base_amount(1_000_000, 1_000_000)
+                    .unwrap(),
+                1_000_000
+            );
+        }
+    }
+
+    mod set_hedge_mode {
+        use super::*;
+
+        #[test]
+        fn live_vault_sets_perp_market_without_perp_position() {
+            let mut vault = Vault {
+                liquidity: 1_000_000,
+                drift_perp_market_index: 0,
+                ..Vault::default()
+            };
+            vault
+                .set_hedge_mode(VaultHedgeMode::SpotBorrow, 1, 500_000, 0)
+                .unwrap();
+            assert_eq!(vault.hedge_mode, VaultHedgeMode::SpotBorrow);
+            assert_eq!(vault.drift_perp_market_index, 1);
+        }
+
+        #[test]
+        fn open_hedge_can_not_be_replaced() {
+            let mut vault = Vault {
+                liquidity: 1_000_000,
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.set_hedge_mode(VaultHedgeMode::PerpShort, 0, 500_000, 0),
+                Err(SurfError::VaultHedgeNotClosed.into())
+            );
+
+            vault.hedge_mode = VaultHedgeMode::PerpShort;
+            assert_eq!(
+                vault.set_hedge_mode(VaultHedgeMode::SpotBorrow, 0, 0, -500_000),
+                Err(SurfError::VaultHedgeNotClosed.into())
+            );
+            assert_eq!(
+                vault.set_hedge_mode(VaultHedgeMode::PerpShort, 1, 0, -500_000),
+                Err(SurfError::VaultHedgeNotClosed.into())
+            );
+        }
+
+        #[test]
+        fn closed_hedge_is_replaced_with_dead_shares_liquidity() {
+            let mut vault = Vault {
+                liquidity: Vault::DEAD_SHARES as u128,
+                ..Vault::default()
+            };
+            vault
+                .set_hedge_mode(VaultHedgeMode::PerpShort, 1, 0, 0)
+                .unwrap();
+            assert_eq!(vault.hedge_mode, VaultHedgeMode::PerpShort);
+            assert_eq!(vault.drift_perp_market_index, 1);
+        }
+    }
+
+    mod set_fees {
+        use super::*;
+        use crate::state::AdminConfig;
+
+        #[test]
+        fn fees_at_maximum() {
+            let mut vault = Vault::default();
+            vault
+                .set_fees(
+                    AdminConfig::MAX_PERFORMANCE_FEE_BPS,
+                    AdminConfig::MAX_MANAGEMENT_FEE_BPS,
+                    AdminConfig::MAX_ENTRY_FEE_BPS,
+                    AdminConfig::MAX_EXIT_FEE_BPS,
+                )
+                .unwrap();
+            assert_eq!(
+                vault.performance_fee_bps,
+                AdminConfig::MAX_PERFORMANCE_FEE_BPS
+            );
+            assert_eq!(
+                vault.management_fee_bps,
+                AdminConfig::MAX_MANAGEMENT_FEE_BPS
+            );
+            assert_eq!(vault.entry_fee_bps, AdminConfig::MAX_ENTRY_FEE_BPS);
+            assert_eq!(vault.exit_fee_bps, AdminConfig::MAX_EXIT_FEE_BPS);
+        }
+
+        #[test]
+        fn fees_above_maximum() {
+            let mut vault = Vault::default();
+            assert_eq!(
+                vault.set_fees(AdminConfig::MAX_PERFORMANCE_FEE_BPS + 1, 0, 0, 0),
+                Err(SurfError::PerformanceFeeTooHigh.into())
+            );
+            assert_eq!(
+                vault.set_fees(0, AdminConfig::MAX_MANAGEMENT_FEE_BPS + 1, 0, 0),
+                Err(SurfError::ManagementFeeTooHigh.into())
+            );
+            assert_eq!(
+                vault.set_fees(0, 0, AdminConfig::MAX_ENTRY_FEE_BPS + 1, 0),
+                Err(SurfError::EntryFeeTooHigh.into())
+            );
+            assert_eq!(
+                vault.set_fees(0, 0, 0, AdminConfig::MAX_EXIT_FEE_BPS + 1),
+                Err(SurfError::ExitFeeTooHigh.into())
+            );
+        }
+    }
+
+    mod harvest_fees {
+        use super::*;
+
+        #[test]
+        fn performance_fee_goes_to_protocol_rest_is_idle() {
+            let mut vault = Vault {
+                performance_fee_bps: 1_000,
+                ..Vault::default()
+            };
+            assert_eq!(vault.harvest_fees(1_000, 2_005).unwrap(), (100, 200));
+            assert_eq!(vault.protocol_fee_base_token_amount, 100);
+            assert_eq!(vault.protocol_fee_quote_token_amount, 200);
+            // Performance fee rounds down in favour of vault
+            assert_eq!(vault.idle_base_token_amount, 900);
+            assert_eq!(vault.idle_quote_token_amount, 1_805);
+
+            assert_eq!(vault.harvest_fees(10, 0).unwrap(), (1, 0));
+            assert_eq!(vault.protocol_fee_base_token_amount, 101);
+            assert_eq!(vault.idle_base_token_amount, 909);
+        }
+
+        #[test]
+        fn zero_performance_fee_compounds_everything() {
+            let mut vault = Vault::default();
+            assert_eq!(vault.harvest_fees(1_000, 2_000).unwrap(), (0, 0));
+            assert_eq!(vault.protocol_fee_base_token_amount, 0);
+            assert_eq!(vault.protocol_fee_quote_token_amount, 0);
+            assert_eq!(vault.idle_base_token_amount, 1_000);
+            assert_eq!(vault.idle_quote_token_amount, 2_000);
+        }
+    }
+
+    mod check_deposit_caps {
+        use super::*;
+
+        fn vault_with_deposit_caps(max_total_deposit: u64, max_deposit_per_user: u64) -> Vault {
+            Vault {
+                total_shares: 1_000_000,
+                max_total_deposit,
+                max_deposit_per_user,
+                ..Vault::default()
+            }
+        }
+
+        #[test]
+        fn zero_caps_are_lifted() {
+            let vault = vault_with_deposit_caps(0, 0);
+            vault.check_deposit_caps(u64::MAX, 1_000_000).unwrap();
+        }
+
+        #[test]
+        fn total_cap() {
+            let vault = vault_with_deposit_caps(2_000_000, 0);
+            vault.check_deposit_caps(2_000_000, 1_000_000).unwrap();
+            assert_eq!(
+                vault.check_deposit_caps(2_000_001, 1_000_000),
+                Err(SurfError::VaultDepositCapExceeded.into())
+            );
+        }
+
+        #[test]
+        fn per_user_cap() {
+            let vault = vault_with_deposit_caps(0, 500_000);
+            // Quarter of shares in vault worth 2_000_000
+            vault.check_deposit_caps(2_000_000, 250_000).unwrap();
+            assert_eq!(
+                vault.check_deposit_caps(2_000_000, 250_001),
+                Err(SurfError::UserDepositCapExceeded.into())
+            );
+        }
+    }
+
+    mod recovery {
+        use super::*;
+        use crate::state::VaultStatus;
+        use drift::state::user::{User as DriftUser, UserStatus};
+
+        fn drift_subaccount(next_liquidation_id: u16, status: UserStatus) -> DriftUser {
+            DriftUser {
+                next_liquidation_id,
+                status,
+                ..DriftUser::default()
+            }
+        }
+
+        #[test]
+        fn healthy_subaccount_keeps_vault_normal() {
+            let mut vault = Vault::default();
+            assert!(!vault.update_status(&drift_subaccount(0, UserStatus::Active)));
+            assert_eq!(vault.status, VaultStatus::Normal);
+        }
+
+        #[test]
+        fn finished_liquidation_starts_recovery() {
+            let mut vault = Vault::default();
+            assert!(vault.update_status(&drift_subaccount(1, UserStatus::Active)));
+            assert_eq!(vault.status, VaultStatus::Recovery);
+
+            // Recovery is entered only once
+            assert!(!vault.update_status(&drift_subaccount(1, UserStatus::Active)));
+        }
+
+        #[test]
+        fn ongoing_liquidation_or_bankruptcy_starts_recovery() {
+            let mut vault = Vault::default();
+            assert!(vault.update_status(&drift_subaccount(0, UserStatus::BeingLiquidated)));
+            assert_eq!(vault.status, VaultStatus::Recovery);
+
+            let mut vault = Vault::default();
+            assert!(vault.update_status(&drift_subaccount(0, UserStatus::Bankrupt)));
+            assert_eq!(vault.status, VaultStatus::Recovery);
+        }
+
+        #[test]
+        fn resolve_recovery_after_liquidation() {
+            let mut vault = Vault::default();
+            assert_eq!(
+                vault.resolve_recovery(&drift_subaccount(0, UserStatus::Active)),
+                Err(SurfError::VaultNotInRecovery.into())
+            );
+
+            vault.update_status(&drift_subaccount(1, UserStatus::BeingLiquidated));
+            assert_eq!(
+                vault.resolve_recovery(&drift_subaccount(1, UserStatus::BeingLiquidated)),
+                Err(SurfError::DriftSubaccountBeingLiquidated.into())
+            );
+            assert_eq!(
+                vault.resolve_recovery(&drift_subaccount(1, UserStatus::Bankrupt)),
+                Err(SurfError::DriftSubaccountBeingLiquidated.into())
+            );
+            assert_eq!(vault.status, VaultStatus::Recovery);
+
+            vault
+                .resolve_recovery(&drift_subaccount(1, UserStatus::Active))
+                .unwrap();
+            assert_eq!(vault.status, VaultStatus::Normal);
+            assert_eq!(vault.drift_next_liquidation_id, 1);
+            // Resolved liquidation does not restart recovery
+            assert!(!vault.update_status(&drift_subaccount(1, UserStatus::Active)));
+        }
+    }
+
+    mod get_drift_collateral_delta {
+        use super::*;
+
+        fn vault_with_idle_quote(idle_quote_token_amount: u64) -> Vault {
+            Vault {
+                target_collateral_ratio: 15_000,
+                idle_quote_token_amount,
+                ..Vault::default()
+            }
+        }
+
+        #[test]
+        fn top_up_limited_by_idle_quote() {
+            let vault = vault_with_idle_quote(1_000_000);
+            assert_eq!(
+                vault
+                    .get_drift_collateral_delta(1_000_000, 1_000_000)
+                    .unwrap(),
+                500_000
+            );
+            let vault = vault_with_idle_quote(100_000);
+            assert_eq!(
+                vault
+                    .get_drift_collateral_delta(1_000_000, 1_000_000)
+                    .unwrap(),
+                100_000
+            );
+        }
+
+        #[test]
+        fn excess_is_withdrawn_without_idle_quote() {
+            let vault = vault_with_idle_quote(0);
+            assert_eq!(
+                vault
+                    .get_drift_collateral_delta(1_000_000, 2_000_000)
+                    .unwrap(),
+                -500_000
+            );
+            assert_eq!(
+                vault
+                    .get_drift_collateral_delta(1_000_000, 1_500_000)
+                    .unwrap(),
+                0
+            );
+        }
+
+        #[test]
+        fn top_up_without_idle_quote() {
+            let vault = vault_with_idle_quote(0);
+            assert_eq!(
+                vault.get_drift_collateral_delta(1_000_000, 1_000_000),
+                Err(SurfError::InsufficientIdleQuoteTokens.into())
+            );
+        }
+    }
+
+    mod validate_drift_delegate {
+        use super::*;
+        use anchor_lang::prelude::Pubkey;
+        use drift::state::user::User as DriftUser;
+
+        #[test]
+        fn subaccount_delegate_matches_vault() {
+            let drift_delegate = Pubkey::new_unique();
+            let vault = Vault {
+                drift_delegate,
+                ..Vault::default()
+            };
+            vault
+                .validate_drift_delegate(&DriftUser {
+                    delegate: drift_delegate,
+                    ..DriftUser::default()
+                })
+                .unwrap();
+
+            // Revoked delegate is default on both sides
+            Vault::default()
+                .validate_drift_delegate(&DriftUser::default())
+                .unwrap();
+        }
+
+        #[test]
+        fn subaccount_delegate_mismatch() {
+            let vault = Vault {
+                drift_delegate: Pubkey::new_unique(),
+                ..Vault::default()
+            };
+            assert_eq!(
+                vault.validate_drift_delegate(&DriftUser {
+                    delegate: Pubkey::new_unique(),
+                    ..DriftUser::default()
+                }),
+                Err(SurfError::DriftDelegateMismatch.into())
+            );
+            assert_eq!(
+                vault.validate_drift_delegate(&DriftUser::default()),
+                Err(SurfError::DriftDelegateMismatch.into())
+            );
+        }
+    }
+
+    mod validate_whirlpool_sqrt_price {
+        use super::*;
+
+        const ORACLE_SQRT_PRICE: u128 = 1 << 64;
+
+        #[test]
+        fn whirlpool_price_within_deviation() {
+            Vault::validate_whirlpool_sqrt_price(ORACLE_SQRT_PRICE, ORACLE_SQRT_PRICE).unwrap();
+            // sqrt(1.0099) and sqrt(0.9901)
+            Vault::validate_whirlpool_sqrt_price(18_537_830_573_005_454_951, ORACLE_SQRT_PRICE)
+                .unwrap();
+            Vault::validate_whirlpool_sqrt_price(18_355_205_569_222_622_374, ORACLE_SQRT_PRICE)
+                .unwrap();
+        }
+
+        #[test]
+        fn whirlpool_price_deviates() {
+            // sqrt(1.0101) and sqrt(0.9899)
+            assert_eq!(
+                Vault::validate_whirlpool_sqrt_price(18_539_666_092_646_866_814, ORACLE_SQRT_PRICE),
+                Err(SurfError::WhirlpoolPriceDeviatesFromOracle.into())
+            );
+            assert_eq!(
+                Vault::validate_whirlpool_sqrt_price(18_353_351_601_684_009_175, ORACLE_SQRT_PRICE),
+                Err(SurfError::WhirlpoolPriceDeviatesFromOracle.into())
+            );
+            assert_eq!(
+                Vault::validate_whirlpool_sqrt_price(u128::MAX, ORACLE_SQRT_PRICE),
+                Err(SurfError::WhirlpoolPriceDeviatesFromOracle.into())
+            );
+        }
+    }
 }
@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+// Shares locked in withdraw request token account until cooldown elapses
+#[account]
+#[derive(Default)]
+pub struct WithdrawRequest {
+    pub bump: u8, // 1
+
+    pub vault: Pubkey, // 32
+    pub owner: Pubkey, // 32
+
+    pub shares: u64,              // 8
+    pub claimable_timestamp: i64, // 8
+}
+
+impl WithdrawRequest {
+    pub const LEN: usize = 8 + 81;
+    pub const NAMESPACE: &'static [u8; 16] = b"withdraw_request";
+}
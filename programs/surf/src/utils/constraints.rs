@@ -1,7 +1,42 @@
 use anchor_lang::prelude::*;
+use drift::state::{perp_market::PerpMarket, spot_market::SpotMarket};
+use whirlpools::TickArray;
 
-use crate::state::AdminConfig;
+use crate::{state::AdminConfig, utils::orca::tick_math::get_start_tick_index};
 
 pub fn is_admin<'info>(admin_config: &Account<'info, AdminConfig>, admin: &Signer<'info>) -> bool {
     admin_config.admin_key.eq(&admin.key())
 }
+
+pub fn is_drift_spot_market<'info>(
+    spot_market: &AccountLoader<'info, SpotMarket>,
+    market_index: u16,
+) -> bool {
+    let (spot_market_address, _) = Pubkey::find_program_address(
+        &[b"spot_market".as_ref(), market_index.to_le_bytes().as_ref()],
+        &drift::ID,
+    );
+    spot_market.key().eq(&spot_market_address)
+}
+
+pub fn is_drift_perp_market<'info>(
+    perp_market: &AccountLoader<'info, PerpMarket>,
+    market_index: u16,
+) -> bool {
+    let (perp_market_address, _) = Pubkey::find_program_address(
+        &[b"perp_market".as_ref(), market_index.to_le_bytes().as_ref()],
+        &drift::ID,
+    );
+    perp_market.key().eq(&perp_market_address)
+}
+
+pub fn is_position_tick_array<'info>(
+    tick_array: &AccountLoader<'info, TickArray>,
+    whirlpool: &Pubkey,
+    tick_index: i32,
+    tick_spacing: u16,
+) -> Result<bool> {
+    let tick_array = tick_array.load()?;
+    Ok(tick_array.whirlpool.eq(whirlpool)
+        && tick_array.start_tick_index == get_start_tick_index(tick_index, tick_spacing))
+}
@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use drift::state::{perp_market::PerpMarket, spot_market::SpotMarket, user::User};
+
+use crate::{
+    errors::SurfError,
+    utils::{
+        drift::{
+            oracle::{get_quote_value, PRICE_PRECISION},
+            perp::{get_perp_position_value, MARGIN_PRECISION},
+            spot_balance::get_spot_token_amount,
+        },
+        math::mul_div,
+    },
+};
+
+// Health ratio of 10_000 means total collateral equals maintenance margin requirement
+pub const HEALTH_RATIO_PRECISION: u64 = 10_000;
+pub const SPOT_WEIGHT_PRECISION: i128 = 10_000;
+
+pub fn get_health_ratio(
+    drift_subaccount: &User,
+    drift_base_spot_market: &SpotMarket,
+    drift_quote_spot_market: &SpotMarket,
+    drift_perp_market: &PerpMarket,
+    base_token_oracle_price: i64,
+) -> Result<u64> {
+    let (total_collateral, maintenance_margin_requirement) = get_margin(
+        drift_subaccount,
+        drift_base_spot_market,
+        drift_quote_spot_market,
+        drift_perp_market,
+        base_token_oracle_price,
+    )?;
+    calculate_health_ratio(total_collateral, maintenance_margin_requirement)
+}
+
+// Total collateral and maintenance margin requirement of vault drift subaccount
+// Only vault spot markets and perp market are used, size based weight adjustments are ignored
+// Perp market is priced with base token oracle
+pub fn get_margin(
+    drift_subaccount: &User,
+    drift_base_spot_market: &SpotMarket,
+    drift_quote_spot_market: &SpotMarket,
+    drift_perp_market: &PerpMarket,
+    base_token_oracle_price: i64,
+) -> Result<(u128, u128)> {
+    let mut total_collateral: i128 = 0;
+    let mut maintenance_margin_requirement: i128 = 0;
+
+    for (spot_market, oracle_price) in [
+        (drift_base_spot_market, base_token_oracle_price),
+        (drift_quote_spot_market, PRICE_PRECISION as i64),
+    ] {
+        let token_amount = get_spot_token_amount(drift_subaccount, spot_market)?;
+        let token_value = get_quote_value(token_amount, oracle_price, spot_market.decimals)?;
+
+        if token_value > 0 {
+            let weighted_value = token_value
+                .checked_mul(spot_market.maintenance_asset_weight as i128)
+                .ok_or(SurfError::MathOverflow)?
+                / SPOT_WEIGHT_PRECISION;
+            total_collateral = total_collateral
+                .checked_add(weighted_value)
+                .ok_or(SurfError::MathOverflow)?;
+        } else {
+            let weighted_value = token_value
+                .checked_neg()
+                .and_then(|value| {
+                    value.checked_mul(spot_market.maintenance_liability_weight as i128)
+                })
+                .ok_or(SurfError::MathOverflow)?
+                / SPOT_WEIGHT_PRECISION;
+            maintenance_margin_requirement = maintenance_margin_requirement
+                .checked_add(weighted_value)
+                .ok_or(SurfError::MathOverflow)?;
+        }
+    }
+
+    let (perp_unrealized_pnl, perp_notional_value) =
+        get_perp_position_value(drift_subaccount, drift_perp_market, base_token_oracle_price)?;
+    total_collateral = total_collateral
+        .checked_add(perp_unrealized_pnl)
+        .ok_or(SurfError::MathOverflow)?;
+    let perp_margin_requirement = i128::try_from(perp_notional_value)
+        .ok()
+        .and_then(|value| value.checked_mul(drift_perp_market.margin_ratio_maintenance as i128))
+        .ok_or(SurfError::MathOverflow)?
+        / MARGIN_PRECISION;
+    maintenance_margin_requirement = maintenance_margin_requirement
+        .checked_add(perp_margin_requirement)
+        .ok_or(SurfError::MathOverflow)?;
+
+    Ok((
+        total_collateral.max(0) as u128,
+        maintenance_margin_requirement as u128,
+    ))
+}
+
+pub fn calculate_health_ratio(
+    total_collateral: u128,
+    maintenance_margin_requirement: u128,
+) -> Result<u64> {
+    if maintenance_margin_requirement == 0 {
+        return Ok(u64::MAX);
+    }
+    let health_ratio = total_collateral
+        .checked_mul(HEALTH_RATIO_PRECISION as u128)
+        .ok_or(SurfError::MathOverflow)?
+        / maintenance_margin_requirement;
+    Ok(u64::try_from(health_ratio).unwrap_or(u64::MAX))
+}
+
+// Liquidity to remove from whirlpool position, so that repaying base borrow with freed base tokens
+// and depositing freed quote tokens as collateral brings health ratio to target
+// target * (requirement - share * base_value * base_weight) = collateral + share * quote_value * quote_weight
+pub fn get_deleverage_liquidity(
+    position_liquidity: u128,
+    position_base_token_value: u128,
+    position_quote_token_value: u128,
+    total_collateral: u128,
+    maintenance_margin_requirement: u128,
+    target_health_ratio: u64,
+    base_liability_weight: u32,
+    quote_asset_weight: u32,
+) -> Option<u128> {
+    let target_health_ratio = target_health_ratio as u128;
+    let spot_weight_precision = SPOT_WEIGHT_PRECISION as u128;
+
+    let required_collateral = target_health_ratio
+        .checked_mul(maintenance_margin_requirement)?
+        .checked_mul(spot_weight_precision)?;
+    let current_collateral = total_collateral
+        .checked_mul(HEALTH_RATIO_PRECISION as u128)?
+        .checked_mul(spot_weight_precision)?;
+    if required_collateral <= current_collateral {
+        return Some(0);
+    }
+    let missing_collateral = required_collateral - current_collateral;
+
+    let position_collateral = position_quote_token_value
+        .checked_mul(quote_asset_weight as u128)?
+        .checked_mul(HEALTH_RATIO_PRECISION as u128)?
+        .checked_add(
+            target_health_ratio
+                .checked_mul(position_base_token_value)?
+                .checked_mul(base_liability_weight as u128)?,
+        )?;
+    if missing_collateral >= position_collateral {
+        return Some(position_liquidity);
+    }
+    mul_div(
+        position_liquidity,
+        missing_collateral,
+        position_collateral,
+        true,
+    )
+}
+
+#[cfg(test)]
+mod test_margin {
+    use crate::utils::drift::margin::{
+        calculate_health_ratio, get_deleverage_liquidity, HEALTH_RATIO_PRECISION,
+    };
+
+    #[test]
+    fn health_ratio_without_margin_requirement() {
+        assert_eq!(calculate_health_ratio(0, 0).unwrap(), u64::MAX);
+        assert_eq!(calculate_health_ratio(1_000, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn health_ratio() {
+        assert_eq!(
+            calculate_health_ratio(1_000, 1_000).unwrap(),
+            HEALTH_RATIO_PRECISION
+        );
+        assert_eq!(calculate_health_ratio(1_500, 1_000).unwrap(), 15_000);
+        assert_eq!(calculate_health_ratio(999, 1_000).unwrap(), 9_990);
+        assert_eq!(calculate_health_ratio(0, 1_000).unwrap(), 0);
+        assert_eq!(
+            calculate_health_ratio(u128::MAX / 10_000, 1).unwrap(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn deleverage_liquidity_reaches_target() {
+        // Collateral 120 and requirement 80 after removing 20% of position
+        assert_eq!(
+            get_deleverage_liquidity(1_000, 100, 100, 100, 100, 15_000, 10_000, 10_000),
+            Some(200)
+        );
+    }
+
+    #[test]
+    fn deleverage_liquidity_is_capped() {
+        assert_eq!(
+            get_deleverage_liquidity(1_000, 100, 100, 150, 100, 15_000, 10_000, 10_000),
+            Some(0)
+        );
+        assert_eq!(
+            get_deleverage_liquidity(1_000, 1, 1, 0, 100, 15_000, 10_000, 10_000),
+            Some(1_000)
+        );
+    }
+}
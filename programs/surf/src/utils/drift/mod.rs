@@ -0,0 +1,5 @@
+pub mod margin;
+pub mod oracle;
+pub mod perp;
+pub mod rates;
+pub mod spot_balance;
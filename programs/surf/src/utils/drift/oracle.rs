@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use drift::state::{
+    oracle::{get_oracle_price as get_drift_oracle_price, OraclePriceData},
+    spot_market::SpotMarket,
+    state::OracleGuardRails,
+};
+
+use crate::{
+    errors::SurfError,
+    utils::math::{mul_div, sqrt_u128},
+};
+
+pub const PRICE_PRECISION: i128 = 1_000_000;
+// Drift guard rails confidence interval max size is a share of price in this precision
+pub const ORACLE_CONFIDENCE_PRECISION: u128 = 1_000_000;
+
+// Oracle price has to pass the same staleness and confidence guard rails as drift margin checks
+pub fn get_oracle_price(
+    oracle: &AccountInfo,
+    spot_market: &SpotMarket,
+    oracle_guard_rails: &OracleGuardRails,
+    clock_slot: u64,
+) -> Result<i64> {
+    let oracle_price_data = get_drift_oracle_price(&spot_market.oracle_source, oracle, clock_slot)
+        .map_err(|_| SurfError::InvalidDriftOraclePrice)?;
+
+    validate_oracle_price_data(
+        &oracle_price_data,
+        oracle_guard_rails.validity.slots_before_stale_for_margin,
+        oracle_guard_rails.validity.confidence_interval_max_size,
+    )?;
+    Ok(oracle_price_data.price)
+}
+
+pub fn validate_oracle_price_data(
+    oracle_price_data: &OraclePriceData,
+    max_delay_slots: i64,
+    max_confidence: u64,
+) -> Result<()> {
+    if oracle_price_data.price <= 0 || !oracle_price_data.has_sufficient_number_of_data_points {
+        return Err(SurfError::InvalidDriftOraclePrice.into());
+    }
+    if oracle_price_data.delay > max_delay_slots {
+        return Err(SurfError::StaleDriftOraclePrice.into());
+    }
+    let confidence = (oracle_price_data.confidence as u128) * ORACLE_CONFIDENCE_PRECISION
+        / (oracle_price_data.price as u128);
+    if confidence > max_confidence as u128 {
+        return Err(SurfError::UncertainDriftOraclePrice.into());
+    }
+    Ok(())
+}
+
+// Oracle price is in PRICE_PRECISION which corresponds with USDC decimals
+pub fn get_quote_value(token_amount: i128, oracle_price: i64, token_decimals: u32) -> Result<i128> {
+    let token_precision = 10_i128
+        .checked_pow(token_decimals)
+        .ok_or(SurfError::NetAssetValueOverflow)?;
+    token_amount
+        .checked_mul(oracle_price as i128)
+        .and_then(|value| value.checked_div(token_precision))
+        .ok_or(SurfError::NetAssetValueOverflow.into())
+}
+
+// Whirlpool Q64.64 sqrt price of oracle price, whirlpool price is quote token units per base token unit
+pub fn get_oracle_sqrt_price(
+    oracle_price: i64,
+    base_token_decimals: u32,
+    quote_token_decimals: u32,
+) -> Result<u128> {
+    let oracle_price =
+        u128::try_from(oracle_price).map_err(|_| SurfError::InvalidDriftOraclePrice)?;
+    let quote_token_precision = 10_u128
+        .checked_pow(quote_token_decimals)
+        .ok_or(SurfError::MathOverflow)?;
+    let base_token_precision = 10_u128
+        .checked_pow(base_token_decimals)
+        .ok_or(SurfError::MathOverflow)?;
+    let price_x64 = oracle_price
+        .checked_mul(quote_token_precision)
+        .zip(base_token_precision.checked_mul(PRICE_PRECISION as u128))
+        .and_then(|(price, precision)| mul_div(price, 1 << 64, precision, false))
+        .ok_or(SurfError::MathOverflow)?;
+    Ok(sqrt_u128(price_x64) << 32)
+}
+
+#[cfg(test)]
+mod test_oracle {
+    use drift::state::oracle::OraclePriceData;
+
+    use crate::{
+        errors::SurfError,
+        utils::drift::oracle::{get_oracle_sqrt_price, validate_oracle_price_data},
+    };
+
+    // 120 slots and 2% of price are drift default guard rails
+    const MAX_DELAY_SLOTS: i64 = 120;
+    const MAX_CONFIDENCE: u64 = 20_000;
+
+    fn oracle_price_data(price: i64, confidence: u64, delay: i64) -> OraclePriceData {
+        OraclePriceData {
+            price,
+            confidence,
+            delay,
+            has_sufficient_number_of_data_points: true,
+        }
+    }
+
+    #[test]
+    fn valid_oracle_price() {
+        let data = oracle_price_data(100_000_000, 2_000_000, MAX_DELAY_SLOTS);
+        assert!(validate_oracle_price_data(&data, MAX_DELAY_SLOTS, MAX_CONFIDENCE).is_ok());
+    }
+
+    #[test]
+    fn stale_oracle_price() {
+        let data = oracle_price_data(100_000_000, 0, MAX_DELAY_SLOTS + 1);
+        assert_eq!(
+            validate_oracle_price_data(&data, MAX_DELAY_SLOTS, MAX_CONFIDENCE),
+            Err(SurfError::StaleDriftOraclePrice.into())
+        );
+    }
+
+    #[test]
+    fn uncertain_oracle_price() {
+        let data = oracle_price_data(100_000_000, 2_000_100, 0);
+        assert_eq!(
+            validate_oracle_price_data(&data, MAX_DELAY_SLOTS, MAX_CONFIDENCE),
+            Err(SurfError::UncertainDriftOraclePrice.into())
+        );
+    }
+
+    #[test]
+    fn invalid_oracle_price() {
+        let data = oracle_price_data(0, 0, 0);
+        assert_eq!(
+            validate_oracle_price_data(&data, MAX_DELAY_SLOTS, MAX_CONFIDENCE),
+            Err(SurfError::InvalidDriftOraclePrice.into())
+        );
+        let data = OraclePriceData {
+            has_sufficient_number_of_data_points: false,
+            ..oracle_price_data(100_000_000, 0, 0)
+        };
+        assert_eq!(
+            validate_oracle_price_data(&data, MAX_DELAY_SLOTS, MAX_CONFIDENCE),
+            Err(SurfError::InvalidDriftOraclePrice.into())
+        );
+    }
+
+    #[test]
+    fn oracle_sqrt_price() {
+        // 1 quote token per base token with equal decimals is sqrt price 1
+        assert_eq!(get_oracle_sqrt_price(1_000_000, 6, 6), Ok(1 << 64));
+        // 20 USDC per 1e9 SOL units is 0.02 quote units per base unit
+        assert_eq!(
+            get_oracle_sqrt_price(20_000_000, 9, 6),
+            Ok(2_608_763_560_792_162_304)
+        );
+        assert_eq!(
+            get_oracle_sqrt_price(-1, 9, 6),
+            Err(SurfError::InvalidDriftOraclePrice.into())
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use drift::{
+    controller::position::PositionDirection,
+    state::{
+        order_params::OrderParams,
+        perp_market::PerpMarket,
+        user::{MarketType, OrderType, User},
+    },
+};
+
+use crate::{
+    errors::SurfError,
+    utils::math::{mul_div, BPS_DENOMINATOR},
+};
+
+// Perp base asset amounts are in BASE_PRECISION, quote asset amounts in PRICE_PRECISION
+pub const BASE_PRECISION: i128 = 1_000_000_000;
+pub const MARGIN_PRECISION: i128 = 10_000;
+
+// Short positions are negative
+pub fn get_perp_base_asset_amount(drift_subaccount: &User, perp_market: &PerpMarket) -> i64 {
+    drift_subaccount
+        .perp_positions
+        .iter()
+        .find(|perp_position| perp_position.market_index == perp_market.market_index)
+        .map_or(0, |perp_position| perp_position.base_asset_amount)
+}
+
+// Unrealized pnl and notional value of perp position denominated in quote token
+pub fn get_perp_position_value(
+    drift_subaccount: &User,
+    perp_market: &PerpMarket,
+    oracle_price: i64,
+) -> Result<(i128, u128)> {
+    let perp_position = drift_subaccount
+        .perp_positions
+        .iter()
+        .find(|perp_position| perp_position.market_index == perp_market.market_index);
+
+    match perp_position {
+        None => Ok((0, 0)),
+        Some(perp_position) => {
+            let base_asset_value = (perp_position.base_asset_amount as i128)
+                .checked_mul(oracle_price as i128)
+                .ok_or(SurfError::DriftPerpPositionError)?
+                / BASE_PRECISION;
+            let unrealized_pnl = base_asset_value
+                .checked_add(perp_position.quote_asset_amount as i128)
+                .ok_or(SurfError::DriftPerpPositionError)?;
+            Ok((unrealized_pnl, base_asset_value.unsigned_abs()))
+        }
+    }
+}
+
+// Immediate or cancel market order, filled against drift amm by place and take.
+// Auction runs from oracle price to limit price, which is oracle price moved against the order
+// direction by max slippage
+pub fn get_perp_market_order_params(
+    direction: PositionDirection,
+    base_asset_amount: u64,
+    market_index: u16,
+    reduce_only: bool,
+    oracle_price: i64,
+    max_slippage_bps: u64,
+) -> Result<OrderParams> {
+    let limit_price = get_perp_order_limit_price(direction, oracle_price, max_slippage_bps)
+        .ok_or(SurfError::MathOverflow)?;
+    let auction_end_price = i64::try_from(limit_price).map_err(|_| SurfError::MathOverflow)?;
+    Ok(OrderParams {
+        order_type: OrderType::Market,
+        market_type: MarketType::Perp,
+        direction,
+        base_asset_amount,
+        price: limit_price,
+        market_index,
+        reduce_only,
+        immediate_or_cancel: true,
+        auction_start_price: Some(oracle_price),
+        auction_end_price: Some(auction_end_price),
+        ..OrderParams::default()
+    })
+}
+
+// Long order pays at most oracle price increased by slippage, short order receives at least
+// oracle price decreased by slippage
+pub fn get_perp_order_limit_price(
+    direction: PositionDirection,
+    oracle_price: i64,
+    max_slippage_bps: u64,
+) -> Option<u64> {
+    let oracle_price = u64::try_from(oracle_price).ok()?;
+    let (slippage_factor, round_up) = match direction {
+        PositionDirection::Long => (BPS_DENOMINATOR.checked_add(max_slippage_bps)?, true),
+        PositionDirection::Short => (BPS_DENOMINATOR.checked_sub(max_slippage_bps)?, false),
+    };
+    let limit_price = mul_div(
+        oracle_price as u128,
+        slippage_factor as u128,
+        BPS_DENOMINATOR as u128,
+        round_up,
+    )?;
+    u64::try_from(limit_price).ok()
+}
+
+// Base token amount converted to perp base asset amount, rounded down to market order step size
+pub fn get_perp_order_base_asset_amount(
+    base_token_amount: u64,
+    base_token_decimals: u32,
+    order_step_size: u64,
+) -> Option<u64> {
+    let token_precision = 10_u128.checked_pow(base_token_decimals)?;
+    let base_asset_amount = (base_token_amount as u128)
+        .checked_mul(BASE_PRECISION as u128)?
+        .checked_div(token_precision)?;
+    let base_asset_amount = u64::try_from(base_asset_amount).ok()?;
+    if order_step_size == 0 {
+        return Some(base_asset_amount);
+    }
+    Some(base_asset_amount - base_asset_amount % order_step_size)
+}
+
+// Perp base asset amount converted to base token amount, rounded down
+pub fn get_base_token_amount_from_perp(
+    base_asset_amount: u64,
+    base_token_decimals: u32,
+) -> Option<u64> {
+    let token_precision = 10_u128.checked_pow(base_token_decimals)?;
+    let base_token_amount = (base_asset_amount as u128)
+        .checked_mul(token_precision)?
+        .checked_div(BASE_PRECISION as u128)?;
+    u64::try_from(base_token_amount).ok()
+}
+
+#[cfg(test)]
+mod test_perp {
+    use drift::controller::position::PositionDirection;
+
+    use crate::utils::drift::perp::{
+        get_base_token_amount_from_perp, get_perp_order_base_asset_amount,
+        get_perp_order_limit_price,
+    };
+
+    #[test]
+    fn perp_order_base_asset_amount() {
+        assert_eq!(
+            get_perp_order_base_asset_amount(1_000_000_000, 9, 0),
+            Some(1_000_000_000)
+        );
+        assert_eq!(
+            get_perp_order_base_asset_amount(1_000_000, 6, 0),
+            Some(1_000_000_000)
+        );
+        assert_eq!(
+            get_perp_order_base_asset_amount(1_234_567_891, 9, 10_000_000),
+            Some(1_230_000_000)
+        );
+        assert_eq!(
+            get_perp_order_base_asset_amount(9_999_999, 9, 10_000_000),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn base_token_amount_from_perp() {
+        assert_eq!(
+            get_base_token_amount_from_perp(1_230_000_000, 9),
+            Some(1_230_000_000)
+        );
+        assert_eq!(
+            get_base_token_amount_from_perp(1_230_000_000, 6),
+            Some(1_230_000)
+        );
+        assert_eq!(get_base_token_amount_from_perp(999, 6), Some(0));
+    }
+
+    #[test]
+    fn perp_order_limit_price() {
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Long, 20_000_000, 100),
+            Some(20_200_000)
+        );
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Short, 20_000_000, 100),
+            Some(19_800_000)
+        );
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Long, 3, 100),
+            Some(4)
+        );
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Short, 3, 100),
+            Some(2)
+        );
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Long, -1, 100),
+            None
+        );
+        assert_eq!(
+            get_perp_order_limit_price(PositionDirection::Short, 20_000_000, 10_001),
+            None
+        );
+    }
+}
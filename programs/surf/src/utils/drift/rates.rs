@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use drift::{
+    math::spot_balance::get_token_amount,
+    state::{
+        perp_market::PerpMarket,
+        spot_market::{SpotBalanceType, SpotMarket},
+    },
+};
+
+use crate::errors::SurfError;
+
+// Annual rates and utilization are in the same precision as drift spot market rates
+pub const SPOT_RATE_PRECISION: u128 = 1_000_000;
+pub const SPOT_UTILIZATION_PRECISION: u128 = 1_000_000;
+// Drift funding rate is in PRICE_PRECISION * FUNDING_RATE_BUFFER per base token
+pub const FUNDING_RATE_BUFFER: i128 = 1_000;
+pub const SECONDS_PER_YEAR: i128 = 365 * 24 * 60 * 60;
+
+// Total deposited and borrowed token amounts of spot market
+pub fn get_spot_market_token_amounts(spot_market: &SpotMarket) -> Result<(u128, u128)> {
+    let deposit_token_amount = get_token_amount(
+        spot_market.deposit_balance,
+        spot_market,
+        &SpotBalanceType::Deposit,
+    )
+    .map_err(|_| SurfError::DriftSpotBalanceError)?;
+    let borrow_token_amount = get_token_amount(
+        spot_market.borrow_balance,
+        spot_market,
+        &SpotBalanceType::Borrow,
+    )
+    .map_err(|_| SurfError::DriftSpotBalanceError)?;
+    Ok((deposit_token_amount, borrow_token_amount))
+}
+
+// Utilization and annual borrow rate of spot market after borrowing additional amount
+pub fn get_spot_market_borrow_conditions(
+    spot_market: &SpotMarket,
+    additional_borrow_amount: u64,
+) -> Result<(u128, u128)> {
+    let (deposit_token_amount, borrow_token_amount) = get_spot_market_token_amounts(spot_market)?;
+    let borrow_token_amount = borrow_token_amount
+        .checked_add(additional_borrow_amount as u128)
+        .ok_or(SurfError::DriftSpotBalanceError)?;
+    let utilization = calculate_utilization(deposit_token_amount, borrow_token_amount);
+    let borrow_rate = calculate_borrow_rate(
+        utilization,
+        spot_market.optimal_utilization as u128,
+        spot_market.optimal_borrow_rate as u128,
+        spot_market.max_borrow_rate as u128,
+    );
+    Ok((utilization, borrow_rate))
+}
+
+// Annual borrow rate of spot market at current utilization
+pub fn get_spot_market_borrow_rate(spot_market: &SpotMarket) -> Result<u128> {
+    let (_, borrow_rate) = get_spot_market_borrow_conditions(spot_market, 0)?;
+    Ok(borrow_rate)
+}
+
+// Annualized funding paid by shorts, negative when shorts receive funding
+pub fn get_perp_short_funding_rate(perp_market: &PerpMarket, oracle_price: i64) -> Result<i128> {
+    calculate_short_funding_rate(
+        perp_market.amm.last_24h_avg_funding_rate,
+        perp_market.amm.funding_period,
+        oracle_price,
+    )
+    .ok_or(SurfError::DriftPerpPositionError.into())
+}
+
+pub fn calculate_utilization(deposit_token_amount: u128, borrow_token_amount: u128) -> u128 {
+    if deposit_token_amount == 0 {
+        return if borrow_token_amount == 0 {
+            0
+        } else {
+            SPOT_UTILIZATION_PRECISION
+        };
+    }
+    (borrow_token_amount * SPOT_UTILIZATION_PRECISION / deposit_token_amount)
+        .min(SPOT_UTILIZATION_PRECISION)
+}
+
+// Borrow rate grows linearly to optimal rate at optimal utilization, then linearly to max rate
+pub fn calculate_borrow_rate(
+    utilization: u128,
+    optimal_utilization: u128,
+    optimal_borrow_rate: u128,
+    max_borrow_rate: u128,
+) -> u128 {
+    if utilization <= optimal_utilization {
+        if optimal_utilization == 0 {
+            return 0;
+        }
+        return utilization * optimal_borrow_rate / optimal_utilization;
+    }
+    let surplus_utilization = utilization - optimal_utilization;
+    let surplus_utilization_range = SPOT_UTILIZATION_PRECISION - optimal_utilization;
+    optimal_borrow_rate
+        + surplus_utilization * max_borrow_rate.saturating_sub(optimal_borrow_rate)
+            / surplus_utilization_range
+}
+
+pub fn calculate_short_funding_rate(
+    funding_rate: i64,
+    funding_period: i64,
+    oracle_price: i64,
+) -> Option<i128> {
+    if funding_period <= 0 || oracle_price <= 0 {
+        return None;
+    }
+    let funding_periods_per_year = SECONDS_PER_YEAR / (funding_period as i128);
+    (funding_rate as i128)
+        .checked_mul(SPOT_RATE_PRECISION as i128)?
+        .checked_mul(funding_periods_per_year)?
+        .checked_div((oracle_price as i128).checked_mul(FUNDING_RATE_BUFFER)?)?
+        .checked_neg()
+}
+
+#[cfg(test)]
+mod test_rates {
+    use crate::utils::drift::rates::{
+        calculate_borrow_rate, calculate_short_funding_rate, calculate_utilization,
+        SPOT_UTILIZATION_PRECISION,
+    };
+
+    #[test]
+    fn utilization() {
+        assert_eq!(calculate_utilization(0, 0), 0);
+        assert_eq!(calculate_utilization(0, 1), SPOT_UTILIZATION_PRECISION);
+        assert_eq!(calculate_utilization(1_000, 250), 250_000);
+        assert_eq!(
+            calculate_utilization(1_000, 2_000),
+            SPOT_UTILIZATION_PRECISION
+        );
+    }
+
+    #[test]
+    fn borrow_rate() {
+        // Optimal utilization 80%, optimal rate 10%, max rate 100%
+        assert_eq!(calculate_borrow_rate(0, 800_000, 100_000, 1_000_000), 0);
+        assert_eq!(
+            calculate_borrow_rate(400_000, 800_000, 100_000, 1_000_000),
+            50_000
+        );
+        assert_eq!(
+            calculate_borrow_rate(800_000, 800_000, 100_000, 1_000_000),
+            100_000
+        );
+        assert_eq!(
+            calculate_borrow_rate(900_000, 800_000, 100_000, 1_000_000),
+            550_000
+        );
+        assert_eq!(
+            calculate_borrow_rate(1_000_000, 800_000, 100_000, 1_000_000),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn short_funding_rate() {
+        // 0.001% of 100 USDC price paid hourly by longs
+        assert_eq!(
+            calculate_short_funding_rate(1_000_000, 3_600, 100_000_000),
+            Some(-87_600)
+        );
+        assert_eq!(
+            calculate_short_funding_rate(-1_000_000, 3_600, 100_000_000),
+            Some(87_600)
+        );
+        assert_eq!(
+            calculate_short_funding_rate(1_000_000, 0, 100_000_000),
+            None
+        );
+    }
+}
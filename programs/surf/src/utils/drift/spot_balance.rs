@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use drift::{
+    math::spot_balance::get_token_amount,
+    state::{
+        spot_market::{SpotBalanceType, SpotMarket},
+        user::User,
+    },
+};
+
+use crate::errors::SurfError;
+
+// Deposits are positive, borrows are negative
+pub fn get_spot_token_amount(drift_subaccount: &User, spot_market: &SpotMarket) -> Result<i128> {
+    let spot_position = drift_subaccount.spot_positions.iter().find(|spot_position| {
+        spot_position.market_index == spot_market.market_index && spot_position.scaled_balance > 0
+    });
+
+    match spot_position {
+        None => Ok(0),
+        Some(spot_position) => {
+            let token_amount = get_token_amount(
+                spot_position.scaled_balance as u128,
+                spot_market,
+                &spot_position.balance_type,
+            )
+            .map_err(|_| SurfError::DriftSpotBalanceError)?;
+            let token_amount =
+                i128::try_from(token_amount).map_err(|_| SurfError::DriftSpotBalanceError)?;
+
+            match spot_position.balance_type {
+                SpotBalanceType::Deposit => Ok(token_amount),
+                SpotBalanceType::Borrow => Ok(-token_amount),
+            }
+        }
+    }
+}
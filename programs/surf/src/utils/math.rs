@@ -0,0 +1,75 @@
+use whirlpools_client::math::U256Muldiv;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// a * b / c without intermediate overflow
+pub fn mul_div(a: u128, b: u128, c: u128, round_up: bool) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    let (result, remainder) = U256Muldiv::new(0, a)
+        .mul(U256Muldiv::new(0, b))
+        .div(U256Muldiv::new(0, c), true);
+    let result = result.try_into_u128().ok()?;
+
+    if round_up && !remainder.is_zero() {
+        return result.checked_add(1);
+    }
+    Some(result)
+}
+
+pub fn mul_div_u64(a: u64, b: u64, c: u64, round_up: bool) -> Option<u64> {
+    let result = mul_div(a as u128, b as u128, c as u128, round_up)?;
+    u64::try_from(result).ok()
+}
+
+// Integer square root rounded down, newton iterations starting above the root decrease onto it
+pub fn sqrt_u128(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut result = value;
+    let mut next = value / 2 + value % 2;
+    while next < result {
+        result = next;
+        next = (result + value / result) / 2;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test_math {
+    use crate::utils::math::{mul_div, mul_div_u64, sqrt_u128};
+
+    #[test]
+    fn mul_div_rounding() {
+        assert_eq!(mul_div(10, 10, 3, false), Some(33));
+        assert_eq!(mul_div(10, 10, 3, true), Some(34));
+        assert_eq!(mul_div(10, 10, 5, true), Some(20));
+    }
+
+    #[test]
+    fn mul_div_does_not_overflow_intermediate() {
+        assert_eq!(mul_div(u128::MAX, 2, 2, false), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 2, 1, false), None);
+        assert_eq!(
+            mul_div_u64(u64::MAX, u64::MAX, u64::MAX, false),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn mul_div_by_zero() {
+        assert_eq!(mul_div(1, 1, 0, false), None);
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        assert_eq!(sqrt_u128(0), 0);
+        assert_eq!(sqrt_u128(1), 1);
+        assert_eq!(sqrt_u128(8), 2);
+        assert_eq!(sqrt_u128(9), 3);
+        assert_eq!(sqrt_u128(1 << 64), 1 << 32);
+        assert_eq!(sqrt_u128(u128::MAX), u64::MAX as u128);
+    }
+}
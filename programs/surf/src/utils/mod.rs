@@ -1,2 +1,4 @@
 pub mod constraints;
+pub mod drift;
+pub mod math;
 pub mod orca;
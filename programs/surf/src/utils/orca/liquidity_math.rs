@@ -1,5 +1,3 @@
-use std::ops::Shr;
-
 use anchor_lang::prelude::*;
 use whirlpools_client::math::{mul_u256, U256Muldiv};
 
@@ -13,14 +11,16 @@ pub fn get_liquidity_from_quote_token(
     sqrt_price_lower: u128,
     sqrt_price_upper: u128,
     round_up: bool,
-) -> u128 {
+) -> Result<u128> {
+    let sqrt_price_range = get_sqrt_price_range(sqrt_price_lower, sqrt_price_upper)?;
+    // u64 shifted by 64 bits always fits into u128
     let numerator = (amount as u128) << 64;
-    let denominator = sqrt_price_upper - sqrt_price_lower;
-    if round_up && numerator % denominator > 0_u128 {
-        return (numerator / denominator) + 1_u128;
-    } else {
-        return numerator / denominator;
+
+    let liquidity = numerator / sqrt_price_range;
+    if round_up && numerator % sqrt_price_range > 0 {
+        return Ok(liquidity + 1);
     }
+    Ok(liquidity)
 }
 
 pub fn get_liquidity_from_base_token(
@@ -29,7 +29,7 @@ pub fn get_liquidity_from_base_token(
     sqrt_price_upper: u128,
     round_up: bool,
 ) -> Result<u128> {
-    let sqrt_price_range = sqrt_price_upper - sqrt_price_lower;
+    let sqrt_price_range = get_sqrt_price_range(sqrt_price_lower, sqrt_price_upper)?;
     // u256 is big enough to perform this calculation
     // Max sqrt price is 96 bits
     // 96 + 96 + 64 = 256
@@ -37,22 +37,29 @@ pub fn get_liquidity_from_base_token(
     let (result_u256, remainder) = sqrt_price_mul
         .mul(U256Muldiv::new(0, amount as u128))
         .div(U256Muldiv::new(0, sqrt_price_range), true);
-    let result = result_u256.try_into_u128();
+    let result = result_u256
+        .shift_right(64)
+        .try_into_u128()
+        .map_err(|_| SurfError::LiquidityOverflow)?;
 
-    if let Err(_) = result {
-        // TODO: Handle
-        return Err(SurfError::BaseTokenOverflow.into());
+    // Result is shifted right, adding one can not overflow
+    if round_up && (!remainder.is_zero() || has_lower_bits(&result_u256)) {
+        return Ok(result + 1);
     }
+    Ok(result)
+}
 
-    dbg!(result.unwrap().shr(64));
-
-    if round_up && !remainder.is_zero() {
-        return Ok((result.unwrap().shr(64) as u128) + 1);
-    } else {
-        return Ok(result.unwrap().shr(64));
+fn get_sqrt_price_range(sqrt_price_lower: u128, sqrt_price_upper: u128) -> Result<u128> {
+    match sqrt_price_upper.checked_sub(sqrt_price_lower) {
+        Some(range) if range > 0 => Ok(range),
+        _ => Err(SurfError::SqrtPriceUnderflow.into()),
     }
 }
 
+fn has_lower_bits(value: &U256Muldiv) -> bool {
+    value.get_word(0) != 0
+}
+
 #[cfg(test)]
 mod test_liquidity_math {
     // Tests based on whirlpools sdk
@@ -82,8 +89,108 @@ mod test_liquidity_math {
                 lower_sqrt_price,
                 upper_sqrt_price,
                 false
-            ),
+            )
+            .unwrap(),
             74085172521_u128,
         )
     }
+
+    mod get_liquidity_round_trip {
+        use proptest::prelude::*;
+        use whirlpools_client::math::{
+            get_amount_delta_a, get_amount_delta_b, sqrt_price_from_tick_index,
+        };
+
+        use crate::utils::orca::{
+            liquidity_math::{get_liquidity_from_base_token, get_liquidity_from_quote_token},
+            tick_math::{MAX_TICK_INDEX, MIN_TICK_INDEX},
+        };
+
+        fn sqrt_price_range() -> impl Strategy<Value = (u128, u128)> {
+            (MIN_TICK_INDEX..MAX_TICK_INDEX)
+                .prop_flat_map(|lower| (Just(lower), (lower + 1)..=MAX_TICK_INDEX))
+                .prop_map(|(lower, upper)| {
+                    (
+                        sqrt_price_from_tick_index(lower),
+                        sqrt_price_from_tick_index(upper),
+                    )
+                })
+        }
+
+        proptest! {
+            #[test]
+            fn quote_token_liquidity_never_exceeds_amount(
+                amount in any::<u64>(),
+                (lower_sqrt_price, upper_sqrt_price) in sqrt_price_range(),
+            ) {
+                let liquidity =
+                    get_liquidity_from_quote_token(amount, lower_sqrt_price, upper_sqrt_price, false)
+                        .unwrap();
+                let required_amount =
+                    get_amount_delta_b(lower_sqrt_price, upper_sqrt_price, liquidity, true).unwrap();
+                prop_assert!(required_amount <= amount);
+            }
+
+            #[test]
+            fn base_token_liquidity_never_exceeds_amount(
+                amount in any::<u64>(),
+                (lower_sqrt_price, upper_sqrt_price) in sqrt_price_range(),
+            ) {
+                // Adjacent max ticks need ~2^110 liquidity per unit of u64, always fits into u128
+                let liquidity =
+                    get_liquidity_from_base_token(amount, lower_sqrt_price, upper_sqrt_price, false);
+                prop_assert!(liquidity.is_ok());
+                let required_amount =
+                    get_amount_delta_a(lower_sqrt_price, upper_sqrt_price, liquidity.unwrap(), true)
+                        .unwrap();
+                prop_assert!(required_amount <= amount);
+            }
+
+            #[test]
+            fn round_up_adds_at_most_one_unit_of_liquidity(
+                amount in any::<u64>(),
+                (lower_sqrt_price, upper_sqrt_price) in sqrt_price_range(),
+            ) {
+                let quote_down =
+                    get_liquidity_from_quote_token(amount, lower_sqrt_price, upper_sqrt_price, false)
+                        .unwrap();
+                let quote_up =
+                    get_liquidity_from_quote_token(amount, lower_sqrt_price, upper_sqrt_price, true)
+                        .unwrap();
+                prop_assert!(quote_up - quote_down <= 1);
+
+                let base_down =
+                    get_liquidity_from_base_token(amount, lower_sqrt_price, upper_sqrt_price, false);
+                let base_up =
+                    get_liquidity_from_base_token(amount, lower_sqrt_price, upper_sqrt_price, true);
+                prop_assert!(base_down.is_ok());
+                prop_assert!(base_up.is_ok());
+                prop_assert!(base_up.unwrap() - base_down.unwrap() <= 1);
+            }
+
+            #[test]
+            fn does_not_panic_in_valid_tick_domain(
+                amount in any::<u64>(),
+                lower_tick_index in MIN_TICK_INDEX..=MAX_TICK_INDEX,
+                upper_tick_index in MIN_TICK_INDEX..=MAX_TICK_INDEX,
+                round_up in any::<bool>(),
+            ) {
+                let lower_sqrt_price = sqrt_price_from_tick_index(lower_tick_index);
+                let upper_sqrt_price = sqrt_price_from_tick_index(upper_tick_index);
+
+                let quote_liquidity =
+                    get_liquidity_from_quote_token(amount, lower_sqrt_price, upper_sqrt_price, round_up);
+                let base_liquidity =
+                    get_liquidity_from_base_token(amount, lower_sqrt_price, upper_sqrt_price, round_up);
+
+                if upper_tick_index <= lower_tick_index {
+                    prop_assert!(quote_liquidity.is_err());
+                    prop_assert!(base_liquidity.is_err());
+                } else {
+                    prop_assert!(quote_liquidity.is_ok());
+                    prop_assert!(base_liquidity.is_ok());
+                }
+            }
+        }
+    }
 }
@@ -1,2 +1,3 @@
 pub mod liquidity_math;
+pub mod position_math;
 pub mod tick_math;
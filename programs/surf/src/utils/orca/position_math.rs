@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use whirlpools_client::math::{get_amount_delta_a, get_amount_delta_b, mul_u256, U256Muldiv};
+
+use crate::errors::SurfError;
+
+// Inverse of liquidity_math, amounts held by liquidity in price range
+// https://github.com/orca-so/whirlpools/blob/0.8.0/sdk/src/utils/position-util.ts
+
+pub fn get_token_amounts_from_liquidity(
+    liquidity: u128,
+    current_sqrt_price: u128,
+    lower_sqrt_price: u128,
+    upper_sqrt_price: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    if upper_sqrt_price <= lower_sqrt_price {
+        return Err(SurfError::SqrtPriceUnderflow.into());
+    }
+
+    // Price below range, position holds only base token
+    if current_sqrt_price < lower_sqrt_price {
+        let base_token_amount =
+            get_amount_delta_a(lower_sqrt_price, upper_sqrt_price, liquidity, round_up)
+                .map_err(|_| SurfError::BaseTokenOverflow)?;
+        return Ok((base_token_amount, 0));
+    }
+
+    // Price above range, position holds only quote token
+    if current_sqrt_price >= upper_sqrt_price {
+        let quote_token_amount =
+            get_amount_delta_b(lower_sqrt_price, upper_sqrt_price, liquidity, round_up)
+                .map_err(|_| SurfError::QuoteTokenOverflow)?;
+        return Ok((0, quote_token_amount));
+    }
+
+    let base_token_amount =
+        get_amount_delta_a(current_sqrt_price, upper_sqrt_price, liquidity, round_up)
+            .map_err(|_| SurfError::BaseTokenOverflow)?;
+    let quote_token_amount =
+        get_amount_delta_b(lower_sqrt_price, current_sqrt_price, liquidity, round_up)
+            .map_err(|_| SurfError::QuoteTokenOverflow)?;
+
+    Ok((base_token_amount, quote_token_amount))
+}
+
+// Price = sqrt_price^2 / 2^128, denominated in quote token units per base token unit
+pub fn get_quote_amount_from_base_token(
+    base_token_amount: u64,
+    sqrt_price: u128,
+    round_up: bool,
+) -> Result<u64> {
+    // Max sqrt price is 96 bits
+    // 96 + 96 + 64 = 256
+    let value = mul_u256(sqrt_price, sqrt_price).mul(U256Muldiv::new(0, base_token_amount as u128));
+    let result = value
+        .shift_right(128)
+        .try_into_u64()
+        .map_err(|_| SurfError::QuoteTokenOverflow)?;
+
+    let has_remainder = value.get_word(0) != 0 || value.get_word(1) != 0;
+    if round_up && has_remainder {
+        return result
+            .checked_add(1)
+            .ok_or(SurfError::QuoteTokenOverflow.into());
+    }
+    Ok(result)
+}
+
+pub fn get_position_value_in_quote(
+    liquidity: u128,
+    current_sqrt_price: u128,
+    lower_sqrt_price: u128,
+    upper_sqrt_price: u128,
+    round_up: bool,
+) -> Result<u64> {
+    let (base_token_amount, quote_token_amount) = get_token_amounts_from_liquidity(
+        liquidity,
+        current_sqrt_price,
+        lower_sqrt_price,
+        upper_sqrt_price,
+        round_up,
+    )?;
+    let base_token_value =
+        get_quote_amount_from_base_token(base_token_amount, current_sqrt_price, round_up)?;
+
+    quote_token_amount
+        .checked_add(base_token_value)
+        .ok_or(SurfError::QuoteTokenOverflow.into())
+}
+
+// Base token exposure of the position, amount of base token which has to be hedged
+pub fn get_position_delta(
+    liquidity: u128,
+    current_sqrt_price: u128,
+    lower_sqrt_price: u128,
+    upper_sqrt_price: u128,
+    round_up: bool,
+) -> Result<u64> {
+    let (base_token_amount, _) = get_token_amounts_from_liquidity(
+        liquidity,
+        current_sqrt_price,
+        lower_sqrt_price,
+        upper_sqrt_price,
+        round_up,
+    )?;
+    Ok(base_token_amount)
+}
+
+#[cfg(test)]
+mod test_position_math {
+    use proptest::prelude::*;
+    use whirlpools_client::math::sqrt_price_from_tick_index;
+
+    use crate::utils::orca::{
+        liquidity_math::{get_liquidity_from_base_token, get_liquidity_from_quote_token},
+        position_math::{
+            get_position_delta, get_position_value_in_quote, get_quote_amount_from_base_token,
+            get_token_amounts_from_liquidity,
+        },
+        tick_math::{MAX_TICK_INDEX, MIN_TICK_INDEX},
+    };
+
+    const UPPER_SQRT_PRICE: u128 = 2857757303569098241;
+    const LOWER_SQRT_PRICE: u128 = 2608763565066556442;
+
+    #[test]
+    fn token_amounts_below_range() {
+        let (base, quote) = get_token_amounts_from_liquidity(
+            1623124806,
+            LOWER_SQRT_PRICE - 1,
+            LOWER_SQRT_PRICE,
+            UPPER_SQRT_PRICE,
+            false,
+        )
+        .unwrap();
+        assert!(base > 0 && base <= 1_000_000_000);
+        assert_eq!(quote, 0);
+    }
+
+    #[test]
+    fn token_amounts_above_range() {
+        let (base, quote) = get_token_amounts_from_liquidity(
+            74085172521,
+            UPPER_SQRT_PRICE,
+            LOWER_SQRT_PRICE,
+            UPPER_SQRT_PRICE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(base, 0);
+        assert!(quote > 0 && quote <= 1_000_000_000);
+    }
+
+    #[test]
+    fn quote_amount_from_base_token_at_price_one() {
+        let sqrt_price_one = 1_u128 << 64;
+        assert_eq!(
+            get_quote_amount_from_base_token(1_000_000, sqrt_price_one, false).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            get_quote_amount_from_base_token(1_000_000, sqrt_price_one, true).unwrap(),
+            1_000_000
+        );
+    }
+
+    fn sqrt_prices() -> impl Strategy<Value = (u128, u128, u128)> {
+        (MIN_TICK_INDEX..MAX_TICK_INDEX)
+            .prop_flat_map(|lower| (Just(lower), (lower + 1)..=MAX_TICK_INDEX))
+            .prop_flat_map(|(lower, upper)| {
+                (
+                    (MIN_TICK_INDEX..=MAX_TICK_INDEX),
+                    Just(lower),
+                    Just(upper),
+                )
+            })
+            .prop_map(|(current, lower, upper)| {
+                (
+                    sqrt_price_from_tick_index(current),
+                    sqrt_price_from_tick_index(lower),
+                    sqrt_price_from_tick_index(upper),
+                )
+            })
+    }
+
+    proptest! {
+        // Token amounts of liquidity below 2^32 fit into u64 over the full tick range
+        #[test]
+        fn rounding_up_never_returns_less(
+            liquidity in 0..u32::MAX as u128,
+            (current, lower, upper) in sqrt_prices(),
+        ) {
+            let down = get_token_amounts_from_liquidity(liquidity, current, lower, upper, false);
+            let up = get_token_amounts_from_liquidity(liquidity, current, lower, upper, true);
+            prop_assert!(down.is_ok());
+            prop_assert!(up.is_ok());
+
+            let (base_down, quote_down) = down.unwrap();
+            let (base_up, quote_up) = up.unwrap();
+            prop_assert!(base_up >= base_down && base_up - base_down <= 1);
+            prop_assert!(quote_up >= quote_down && quote_up - quote_down <= 1);
+        }
+
+        #[test]
+        fn round_trip_never_returns_more_than_supplied(
+            amount in any::<u64>(),
+            (_, lower, upper) in sqrt_prices(),
+        ) {
+            // Below range the position is made of base token only
+            let liquidity = get_liquidity_from_base_token(amount, lower, upper, false);
+            prop_assert!(liquidity.is_ok());
+            let (base, quote) =
+                get_token_amounts_from_liquidity(liquidity.unwrap(), lower - 1, lower, upper, false)
+                    .unwrap();
+            prop_assert!(base <= amount);
+            prop_assert_eq!(quote, 0);
+
+            // Above range the position is made of quote token only
+            let liquidity = get_liquidity_from_quote_token(amount, lower, upper, false);
+            prop_assert!(liquidity.is_ok());
+            let (base, quote) =
+                get_token_amounts_from_liquidity(liquidity.unwrap(), upper, lower, upper, false)
+                    .unwrap();
+            prop_assert_eq!(base, 0);
+            prop_assert!(quote <= amount);
+        }
+
+        #[test]
+        fn position_value_and_delta_respect_rounding(
+            liquidity in 0..u32::MAX as u128,
+            (current, lower, upper) in sqrt_prices(),
+        ) {
+            let delta_down = get_position_delta(liquidity, current, lower, upper, false);
+            let delta_up = get_position_delta(liquidity, current, lower, upper, true);
+            prop_assert!(delta_down.is_ok());
+            prop_assert!(delta_up.is_ok());
+
+            let (delta_down, delta_up) = (delta_down.unwrap(), delta_up.unwrap());
+            prop_assert!(delta_up >= delta_down);
+            if current >= upper {
+                prop_assert_eq!(delta_down, 0);
+            }
+
+            // Near the max tick a single unit of base token is worth almost u64::MAX quote,
+            // rounding it up can push the value out of u64
+            let value_up = get_position_value_in_quote(liquidity, current, lower, upper, true);
+            prop_assume!(value_up.is_ok());
+            let value_down = get_position_value_in_quote(liquidity, current, lower, upper, false);
+            prop_assert!(value_down.is_ok());
+            prop_assert!(value_up.unwrap() >= value_down.unwrap());
+        }
+
+        #[test]
+        fn does_not_panic_in_valid_tick_domain(
+            liquidity in any::<u128>(),
+            current_tick_index in MIN_TICK_INDEX..=MAX_TICK_INDEX,
+            lower_tick_index in MIN_TICK_INDEX..=MAX_TICK_INDEX,
+            upper_tick_index in MIN_TICK_INDEX..=MAX_TICK_INDEX,
+            round_up in any::<bool>(),
+        ) {
+            let current = sqrt_price_from_tick_index(current_tick_index);
+            let lower = sqrt_price_from_tick_index(lower_tick_index);
+            let upper = sqrt_price_from_tick_index(upper_tick_index);
+
+            let amounts = get_token_amounts_from_liquidity(liquidity, current, lower, upper, round_up);
+            let _ = get_position_value_in_quote(liquidity, current, lower, upper, round_up);
+            let _ = get_position_delta(liquidity, current, lower, upper, round_up);
+
+            if upper_tick_index <= lower_tick_index {
+                prop_assert!(amounts.is_err());
+            }
+        }
+    }
+}
@@ -1,11 +1,18 @@
 // https://github.com/orca-so/whirlpools
 pub const MAX_TICK_INDEX: i32 = 443636;
 pub const MIN_TICK_INDEX: i32 = -443636;
+pub const TICK_ARRAY_SIZE: i32 = 88;
 
 pub fn get_initializable_tick_index(tick_index: i32, tick_spacing: u16) -> i32 {
     tick_index - (tick_index % tick_spacing as i32)
 }
 
+// Start tick index of the tick array containing tick_index
+pub fn get_start_tick_index(tick_index: i32, tick_spacing: u16) -> i32 {
+    let ticks_in_array = TICK_ARRAY_SIZE * tick_spacing as i32;
+    tick_index.div_euclid(ticks_in_array) * ticks_in_array
+}
+
 #[cfg(test)]
 mod test_tick_math {
 
@@ -24,4 +31,23 @@ mod test_tick_math {
             assert_eq!(get_initializable_tick_index(-1299, 64), -1280);
         }
     }
+
+    mod get_start_tick_index {
+        use crate::utils::orca::tick_math::get_start_tick_index;
+
+        #[test]
+        fn positive_tick_index() {
+            assert_eq!(get_start_tick_index(0, 8), 0);
+            assert_eq!(get_start_tick_index(703, 8), 0);
+            assert_eq!(get_start_tick_index(704, 8), 704);
+            assert_eq!(get_start_tick_index(6000, 64), 5632);
+        }
+
+        #[test]
+        fn negative_tick_index() {
+            assert_eq!(get_start_tick_index(-1, 8), -704);
+            assert_eq!(get_start_tick_index(-704, 8), -704);
+            assert_eq!(get_start_tick_index(-705, 8), -1408);
+        }
+    }
 }